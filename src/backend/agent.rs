@@ -1,74 +1,136 @@
-use std::borrow::BorrowMut;
-use std::cell::RefCell;
-use std::rc::Rc;
-use tch::Tensor;
-use crate::backend::engine::Engine;
+use std::collections::HashSet;
+use serde::{Deserialize, Serialize};
+use crate::backend::error::{Error, Result};
 use crate::backend::gene::Genome;
 use crate::backend::Position;
 
-use crate::backend::map::{Action, Direction};
-use crate::backend::map::Action::{Move, Reproduce};
+use crate::backend::map::Action;
+use crate::backend::policy::{Policy, PolicyMode};
 
+/// A genome placed on the map for one match, with what it has sensed and done so far.
 #[derive(Debug)]
 pub struct Agent {
+	/// the id of the genome it plays, or a made-up one for scripted and idle agents
 	pub id: u64,
+	/// the tile it stands on
 	pub position: Position,
-	pub genome: Rc<RefCell<Genome>>,
-	pub current_sense: Option<AgentSense>
+	/// unset for scripted and idle agents, see Agent::scripted and Agent::idle
+	pub genome: Option<Genome>,
+	/// acts in place of the genome when set, see Agent::scripted
+	pub policy: Option<Box<dyn Policy>>,
+	/// what it saw at the start of the round; none before its first
+	pub current_sense: Option<AgentSense>,
+	/// stats for the match this agent is playing in; merged into the genome pool afterwards
+	pub stats: AgentStats,
+	/// behaviour tracking for the current match, see Behaviour
+	pub visited: HashSet<Position>,
+	/// how often it took each action, indexed by Action::index
+	pub action_counts: [usize; Action::COUNT]
 }
 
 
-#[derive(Debug, Clone, Copy, Hash)]
+/// Which tiles around the agent hold food: a row-major square of EngineConfig::vision_length per side.
+#[derive(Debug, Clone, Hash)]
 pub struct AgentSense {
+	/// the tile the agent stood on when it sensed
 	pub position: Position,
-	pub map_tiles: [bool; Engine::DISTANCE_VISIBLE_BLOCKS]
+	/// whether each tile holds food
+	pub map_tiles: Vec<bool>
 }
 
-#[derive(Debug, Clone, Copy, Hash)]
+impl AgentSense {
+	/// The tiles as a network input: 1 for food, 0 for nothing.
+	pub fn input(&self) -> Vec<f32> {
+		self.map_tiles
+			.iter()
+			.map(|tile| if *tile { 1f32 } else { 0f32 })
+			.collect()
+	}
+}
+
+/// What an agent did in a single match.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Serialize, Deserialize)]
 pub struct AgentStats {
+	/// food tiles it stepped onto
 	pub food_eaten: usize,
-	pub cumulative_food_eaten: usize,
-	pub steps_taken: usize,
-	pub parent: Option<u64>,
-	pub generation: usize
+	/// rounds it took an action in
+	pub steps_taken: usize
 }
 
 impl AgentStats {
+	/// Stats for a match that hasn't started.
 	pub fn new() -> Self {
 		Self {
 			food_eaten: 0,
-			cumulative_food_eaten: 0,
-			steps_taken: 0,
-			parent: None,
-			generation: 0
+			steps_taken: 0
 		}
 	}
 }
 
+impl Default for AgentStats {
+	fn default() -> Self {
+		AgentStats::new()
+	}
+}
+
 impl Agent {
-	pub fn get_action(&mut self) -> Action {
-		self.genome.as_ref().borrow_mut().stats.steps_taken += 1;
+	/// An agent acting on `genome`'s network.
+	pub fn new(id: u64, position: Position, genome: Genome) -> Self {
+		Self {
+			genome: Some(genome),
+			..Agent::idle(id, position)
+		}
+	}
 
-		let direction_idx = self.genome.borrow().forward(&self.build_input_tensor());
+	/// An agent with neither a genome nor a policy. It stays put unless the caller supplies its
+	/// actions, as an Environment does.
+	pub fn idle(id: u64, position: Position) -> Self {
+		Self {
+			id,
+			position,
+			genome: None,
+			policy: None,
+			current_sense: None,
+			stats: AgentStats::new(),
+			visited: HashSet::from([position]),
+			action_counts: [0; Action::COUNT]
+		}
+	}
 
-		return match direction_idx {
-			0 => Move(Direction::Up),
-			1 => Move(Direction::Down),
-			2 => Move(Direction::Left),
-			3 => Move(Direction::Right),
-			_ => {Reproduce}
+	/// An agent controlled by `policy` rather than evolved.
+	pub fn scripted(id: u64, position: Position, policy: Box<dyn Policy>) -> Self {
+		Self {
+			policy: Some(policy),
+			..Agent::idle(id, position)
 		}
 	}
 
-	fn build_input_tensor(&self) -> Tensor {
-		let t = Tensor::of_slice(&self.current_sense.unwrap().map_tiles)
-			.f_internal_cast_float(false)
-			.unwrap()
-			.unsqueeze(0);
-		t
+	/// Moves the agent to `position`, counting `action` towards its behaviour.
+	pub fn record_move(&mut self, action: &Action, position: Position) {
+		self.position = position;
+		self.visited.insert(position);
+		self.action_counts[action.index()] += 1;
+	}
+
+	/// Picks the agent's next action from its current sense, asking its policy if it has one and
+	/// its genome otherwise. Idle agents stay put.
+	pub fn get_action(&self, mode: PolicyMode) -> Result<Action> {
+		let sense = self.current_sense.as_ref().ok_or(Error::NotSensed(self.id))?;
+		Ok(match (&self.policy, &self.genome) {
+			(Some(policy), _) => policy.act(sense, mode),
+			(None, Some(genome)) => genome.act(sense, mode),
+			(None, None) => Action::Reproduce
+		})
+	}
+
+	/// The network input for the agent's current sense; fails before its first sense.
+	pub(crate) fn build_input(&self) -> Result<Vec<f32>> {
+		let sense = self.current_sense.as_ref().ok_or(Error::NotSensed(self.id))?;
+		Ok(sense.input())
 	}
 
+	/// Counts one more food tile eaten.
 	pub fn increment_food(&mut self) {
-		self.genome.as_ref().borrow_mut().stats.food_eaten += 1;
+		self.stats.food_eaten += 1;
 	}
 }
\ No newline at end of file
@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use crate::backend::error::{Error, Result};
+use crate::backend::gene::{Ancestry, Genome};
+use crate::backend::network::NetworkShape;
+
+/// Everything needed to rebuild a Genome, in a form that can be written to disk.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenomeRecord {
+	/// see Genome::id
+	pub id: u64,
+	/// see Ancestry::parents
+	pub parents: Vec<u64>,
+	/// see Ancestry::mutation
+	pub mutation: Option<String>,
+	/// records written before the shape was configurable all have the default one
+	#[serde(default)]
+	pub shape: NetworkShape,
+	/// the network's weights, flattened as Genome::weights does
+	pub weights: Vec<f32>,
+	/// one self-adaptive mutation step size per weight
+	pub step_sizes: Vec<f32>,
+	/// the EngineConfig::vision_radius the genome evolved with, for standalone genome files
+	/// such as best_genome.json; the shape's inputs only fit engines with this radius
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub vision_radius: Option<usize>
+}
+
+impl GenomeRecord {
+	/// Records `genome`, leaving `vision_radius` for the caller to fill in.
+	pub fn from_genome(genome: &Genome) -> Self {
+		Self {
+			id: genome.id,
+			parents: genome.ancestry.parents.clone(),
+			mutation: genome.ancestry.mutation.clone(),
+			shape: genome.shape(),
+			weights: genome.weights(),
+			step_sizes: genome.step_sizes.clone(),
+			vision_radius: None
+		}
+	}
+
+	/// A single genome as a standalone JSON file, e.g. for `cadmus eval`.
+	pub fn save(&self, path: &Path) -> Result<()> {
+		let mut writer = BufWriter::new(File::create(path)?);
+		serde_json::to_writer_pretty(&mut writer, self)?;
+		Ok(writer.flush()?)
+	}
+
+	/// Reads a genome file written by `save`; check it with `to_genome` before use.
+	pub fn load(path: &Path) -> Result<Self> {
+		Ok(serde_json::from_reader(BufReader::new(File::open(path)?))?)
+	}
+
+	/// Rebuilds the genome for a network of `shape`. Fails if the record was saved for another shape,
+	/// or if its weights or step sizes don't fill that shape, as in a truncated file.
+	pub fn to_genome(&self, shape: NetworkShape) -> Result<Genome> {
+		if self.shape != shape {
+			return Err(Error::InvalidGenome(format!("genome {} has shape {:?} but {:?} is needed", self.id, self.shape, shape)));
+		}
+		let weight_count = shape.weight_count();
+		if self.weights.len() != weight_count || self.step_sizes.len() != weight_count {
+			return Err(Error::InvalidGenome(format!(
+				"genome {} has {} weights and {} step sizes but its shape takes {}",
+				self.id,
+				self.weights.len(),
+				self.step_sizes.len(),
+				weight_count
+			)));
+		}
+
+		let mut genome = Genome::new(self.id, self.shape);
+		genome.set_weights(&self.weights);
+		genome.step_sizes = self.step_sizes.clone();
+		genome.ancestry = Ancestry {
+			parents: self.parents.clone(),
+			mutation: self.mutation.clone()
+		};
+		Ok(genome)
+	}
+}
+
+/// Append-only JSON Lines file of genomes evicted from a GenomePool, indexed by id so single
+/// genomes can be read back without loading the whole file.
+#[derive(Debug)]
+pub struct GenomeArchive {
+	path: PathBuf,
+	file: File,
+	index: HashMap<u64, u64>
+}
+
+impl GenomeArchive {
+	/// Opens the archive at `path`, creating it if needed and indexing any genomes already in it.
+	pub fn open(path: &Path) -> Result<Self> {
+		let file = OpenOptions::new()
+			.create(true)
+			.read(true)
+			.append(true)
+			.open(path)?;
+
+		let mut index = HashMap::new();
+		let mut reader = BufReader::new(&file);
+		let mut offset = 0u64;
+		let mut line = String::new();
+		loop {
+			line.clear();
+			let read = reader.read_line(&mut line)?;
+			if read == 0 {
+				break;
+			}
+			let record: GenomeRecord = serde_json::from_str(&line)?;
+			index.insert(record.id, offset);
+			offset += read as u64;
+		}
+
+		Ok(Self {
+			path: path.to_path_buf(),
+			file,
+			index
+		})
+	}
+
+	/// The file the archive appends to.
+	pub fn path(&self) -> &Path {
+		&self.path
+	}
+
+	/// Number of genomes archived.
+	pub fn len(&self) -> usize {
+		self.index.len()
+	}
+
+	/// Whether nothing has been archived yet.
+	pub fn is_empty(&self) -> bool {
+		self.index.is_empty()
+	}
+
+	/// Whether the genome `id` has been archived.
+	pub fn contains(&self, id: u64) -> bool {
+		self.index.contains_key(&id)
+	}
+
+	/// Appends `genome` to the file, unless it is archived already.
+	pub fn store(&mut self, genome: &Genome) -> Result<()> {
+		if self.contains(genome.id) {
+			return Ok(());
+		}
+
+		let mut line = serde_json::to_string(&GenomeRecord::from_genome(genome))?;
+		line.push('\n');
+		let offset = self.file.seek(SeekFrom::End(0))?;
+		self.file.write_all(line.as_bytes())?;
+		self.index.insert(genome.id, offset);
+		Ok(())
+	}
+
+	/// Reads back the genome `id`, or None if it was never archived.
+	pub fn load(&self, id: u64) -> Result<Option<Genome>> {
+		let Some(offset) = self.index.get(&id) else {
+			return Ok(None);
+		};
+
+		let mut reader = BufReader::new(&self.file);
+		reader.seek(SeekFrom::Start(*offset))?;
+		let mut line = String::new();
+		reader.read_line(&mut line)?;
+		let record: GenomeRecord = serde_json::from_str(&line)?;
+		Ok(Some(record.to_genome(record.shape)?))
+	}
+}
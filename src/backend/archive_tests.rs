@@ -0,0 +1,108 @@
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod archive_tests {
+	use std::fs;
+	use crate::backend::agent::AgentStats;
+	use crate::backend::archive::{GenomeArchive, GenomeRecord};
+	use crate::backend::error::Error;
+	use crate::backend::evaluation::{Aggregation, Evaluation};
+	use crate::backend::gene::{Genome, GenomePool, RetentionPolicy};
+	use crate::backend::lineage::Lineage;
+	use crate::backend::network::NetworkShape;
+
+	fn evaluation(food_eaten: &[usize]) -> Evaluation {
+		let trials = food_eaten.iter()
+			.map(|food| AgentStats { food_eaten: *food, steps_taken: 10 })
+			.collect();
+		Evaluation::new(trials, Vec::new(), Aggregation::Mean)
+	}
+
+	#[test]
+	fn evicts_to_archive_and_reloads() {
+		let path = std::env::temp_dir().join(format!("cadmus_archive_{}.jsonl", std::process::id()));
+		let _ = fs::remove_file(&path);
+
+		let mut genepool = GenomePool::with_archive(GenomeArchive::open(&path).unwrap());
+		let root = Genome::blank(1);
+		let stale = Genome::blank(2);
+		genepool.add_genome(root.clone());
+		genepool.add_genome(stale.clone());
+		genepool.record_evaluation(root.id, 0, &evaluation(&[5]));
+		genepool.record_evaluation(stale.id, 0, &evaluation(&[1]));
+
+		genepool.set_generation(1);
+		let child = root.copy();
+		genepool.add_genome(child.clone());
+
+		let mut lineage = Lineage::new();
+		lineage.record_birth(&root, 0);
+		lineage.record_birth(&stale, 0);
+		lineage.record_birth(&child, 1);
+
+		let policy = RetentionPolicy { elites: 0, ancestor_depth: 1, history: 4 };
+		assert_eq!(genepool.retain(&[child.id], policy, &lineage).unwrap(), 1);
+		assert!(genepool.contains(root.id));
+		assert!(!genepool.contains(stale.id));
+
+		// the evicted genome's summary and history go with it
+		assert_eq!(genepool.by_generation(0), vec![root.id]);
+		assert_eq!(genepool.by_generation(1), vec![child.id]);
+		assert_eq!(genepool.top_by_fitness(2), vec![root.id]);
+		assert!(genepool.summary(stale.id).is_none() && genepool.history(stale.id).is_empty());
+
+		let reloaded = genepool.load(stale.id).unwrap();
+		assert_eq!(reloaded.weights(), stale.weights());
+
+		// reopening indexes what is already on disk
+		let archive = GenomeArchive::open(&path).unwrap();
+		assert!(archive.contains(stale.id));
+		fs::remove_file(&path).unwrap();
+	}
+
+	#[test]
+	fn keeps_history_per_generation() {
+		let mut genepool = GenomePool::new();
+		let elite = Genome::blank(1);
+		genepool.add_genome(elite.clone());
+		genepool.record_evaluation(elite.id, 0, &evaluation(&[4, 2]));
+
+		genepool.set_generation(1);
+		let child = elite.copy();
+		genepool.add_genome(child.clone());
+		genepool.record_evaluation(elite.id, 1, &evaluation(&[1, 1]));
+		genepool.record_evaluation(child.id, 1, &evaluation(&[2, 2]));
+
+		// children start with a clean history rather than inheriting their parent's food
+		assert_eq!(genepool.cumulative_food_eaten(child.id), 4);
+		assert_eq!(genepool.cumulative_food_eaten(elite.id), 8);
+		assert_eq!(genepool.history(elite.id)[0].fitness, 3.0);
+		assert_eq!(genepool.latest(elite.id).unwrap().fitness, 1.0);
+		assert_eq!(genepool.latest(child.id).unwrap().steps_taken(), 20);
+		assert_eq!(genepool.generation_history(1).len(), 2);
+		assert_eq!(genepool.top_by_fitness(1), vec![elite.id]);
+
+		// only the most recent evaluations are kept
+		for generation in 2..10 {
+			genepool.record_evaluation(elite.id, generation, &evaluation(&[1]));
+		}
+		let mut lineage = Lineage::new();
+		lineage.record_birth(&elite, 0);
+		lineage.record_birth(&child, 1);
+		let policy = RetentionPolicy { elites: 1, ancestor_depth: 1, history: 3 };
+		genepool.retain(&[child.id], policy, &lineage).unwrap();
+		let generations = genepool.history(elite.id).iter().map(|stats| stats.generation).collect::<Vec<usize>>();
+		assert_eq!(generations, vec![7, 8, 9]);
+	}
+
+	#[test]
+	fn rejects_records_that_dont_fit() {
+		let genome = Genome::blank(3);
+		let mut record = GenomeRecord::from_genome(&genome);
+		assert_eq!(record.to_genome(NetworkShape::DEFAULT).unwrap().weights(), genome.weights());
+		assert!(matches!(record.to_genome(NetworkShape { inputs: 9, hidden: 32 }), Err(Error::InvalidGenome(_))));
+
+		// as in a truncated best_genome.json
+		record.weights.pop();
+		assert!(matches!(record.to_genome(NetworkShape::DEFAULT), Err(Error::InvalidGenome(_))));
+	}
+}
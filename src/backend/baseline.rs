@@ -0,0 +1,73 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use crate::backend::agent::AgentSense;
+use crate::backend::map::{Action, Direction};
+use crate::backend::policy::{Policy, PolicyMode};
+use crate::backend::random::rng;
+
+/// Fixed, unevolved behaviours that give evolved genomes a reference score to beat. They can play
+/// on their own (see Orchestrator::benchmark_baseline) or alongside a population (see
+/// OrchestratorConfig::baselines).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Baseline {
+	/// a uniformly random move every round
+	RandomWalk,
+	/// a step towards the nearest food in sight, or a random move when there is none
+	GreedyFood,
+	/// never moves, so only eats food that starts under it
+	StayStill
+}
+
+impl Baseline {
+	/// Every baseline, in the order benchmarks report them.
+	pub const ALL: [Baseline; 3] = [Baseline::RandomWalk, Baseline::GreedyFood, Baseline::StayStill];
+
+	/// The baseline's name in configs and reports.
+	pub fn name(&self) -> &'static str {
+		match self {
+			Baseline::RandomWalk => "random_walk",
+			Baseline::GreedyFood => "greedy_food",
+			Baseline::StayStill => "stay_still"
+		}
+	}
+}
+
+impl Policy for Baseline {
+	fn act(&self, sense: &AgentSense, _mode: PolicyMode) -> Action {
+		match self {
+			Baseline::RandomWalk => random_move(),
+			Baseline::GreedyFood => nearest_food(sense).map_or_else(random_move, towards),
+			// Reproduce leaves the agent where it is
+			Baseline::StayStill => Action::Reproduce
+		}
+	}
+}
+
+fn random_move() -> Action {
+	Action::from_index(rng().gen_range(0..4))
+}
+
+// Offset of the closest visible food by Manhattan distance, ties going to the first in row order.
+fn nearest_food(sense: &AgentSense) -> Option<(i32, i32)> {
+	let length = (sense.map_tiles.len() as f64).sqrt().round() as usize;
+	let radius = (length / 2) as i32;
+	sense.map_tiles.iter()
+		.enumerate()
+		.filter(|(_, food)| **food)
+		.map(|(idx, _)| ((idx % length) as i32 - radius, (idx / length) as i32 - radius))
+		.filter(|offset| *offset != (0, 0))
+		.min_by_key(|(x, y)| x.abs() + y.abs())
+}
+
+// A move that closes the longer axis of `offset` first.
+fn towards((x, y): (i32, i32)) -> Action {
+	let direction = if x.abs() >= y.abs() {
+		if x > 0 { Direction::Right } else { Direction::Left }
+	} else if y > 0 {
+		Direction::Down
+	} else {
+		Direction::Up
+	};
+	Action::Move(direction)
+}
@@ -0,0 +1,66 @@
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod baseline_tests {
+	use crate::backend::agent::AgentSense;
+	use crate::backend::baseline::Baseline;
+	use crate::backend::engine::EngineConfig;
+	use crate::backend::map::{Action, Direction};
+	use crate::backend::policy::{Policy, PolicyMode};
+	use crate::backend::random;
+	use crate::backend::{Orchestrator, OrchestratorConfig, Position};
+
+	fn sense(food: &[(i32, i32)]) -> AgentSense {
+		let mut map_tiles = vec![false; 25];
+		for (x, y) in food {
+			map_tiles[(x + 2) as usize + (y + 2) as usize * 5] = true;
+		}
+		AgentSense { position: Position::new(0, 0), map_tiles }
+	}
+
+	#[test]
+	fn greedy_heads_for_the_nearest_food() {
+		let mode = PolicyMode::Greedy;
+		assert!(Baseline::GreedyFood.act(&sense(&[(2, 2), (-1, 0)]), mode) == Action::Move(Direction::Left));
+		assert!(Baseline::GreedyFood.act(&sense(&[(1, -2)]), mode) == Action::Move(Direction::Up));
+		assert!(Baseline::GreedyFood.act(&sense(&[(2, 1)]), mode) == Action::Move(Direction::Right));
+		assert!(Baseline::StayStill.act(&sense(&[(0, 1)]), mode) == Action::Reproduce);
+	}
+
+	#[test]
+	fn baselines_play_alongside_the_population() {
+		random::seed(5);
+		let engine_config = EngineConfig {
+			size: [20f64, 20f64],
+			round_max: 30,
+			agent_count: 6,
+			..EngineConfig::default()
+		};
+		let config = OrchestratorConfig {
+			trials: 2,
+			threads: 1,
+			baselines: vec![Baseline::GreedyFood, Baseline::RandomWalk],
+			..OrchestratorConfig::default()
+		};
+		let mut orchestrator = Orchestrator::with_config(engine_config, config).unwrap();
+
+		let evaluations = orchestrator.run_generation(0).unwrap();
+		assert_eq!(evaluations.len(), 6);
+		assert!(evaluations.keys().all(|id| orchestrator.genepool.contains(*id)));
+
+		// baselines are driven by their policy alone and carry no genome
+		let agents = orchestrator.build_agents(&Orchestrator::population(&evaluations)).unwrap();
+		assert_eq!(agents.len(), 6 + 2);
+		assert!(agents.values().all(|agent| agent.genome.is_some() != agent.policy.is_some()));
+
+		// a match set up for watching hasn't been played yet
+		let engine = orchestrator.begin_match(&Orchestrator::population(&evaluations), PolicyMode::Greedy).unwrap();
+		assert_eq!(engine.agents.len(), 6 + 2);
+		assert_eq!(engine.round_idx, 0);
+
+		let still = orchestrator.benchmark_baseline(Baseline::StayStill, 2).unwrap();
+		let greedy = orchestrator.benchmark_baseline(Baseline::GreedyFood, 2).unwrap();
+		assert_eq!(greedy.trials.len(), 2 * 6);
+		assert!(still.trials.iter().all(|stats| stats.food_eaten <= 1));
+		assert!(greedy.food > still.food, "greedy {} vs still {}", greedy.food, still.food);
+	}
+}
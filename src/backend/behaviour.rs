@@ -0,0 +1,159 @@
+use serde::{Deserialize, Serialize};
+use crate::backend::agent::Agent;
+use crate::backend::map::Action;
+
+/// What an agent did during a match, independent of how much food it found.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Behaviour {
+	/// normalised to [0, 1] by the map size
+	pub final_position: [f64; 2],
+	/// distinct tiles visited per step taken
+	pub coverage: f64,
+	/// fraction of steps spent on each action, indexed by Action::index
+	pub action_histogram: [f64; Action::COUNT]
+}
+
+impl Behaviour {
+	/// Length of a descriptor.
+	pub const DIMENSIONS: usize = 3 + Action::COUNT;
+	/// Names of a descriptor's dimensions, in order; MAP-Elites dimensions index into these.
+	pub const DIMENSION_NAMES: [&'static str; Behaviour::DIMENSIONS] =
+		["final_x", "final_y", "coverage", "up", "down", "left", "right", "reproduce"];
+
+	/// The behaviour of `agent` so far, on a map of `size`.
+	pub fn from_agent(agent: &Agent, size: [f64; 2]) -> Self {
+		let steps = agent.action_counts.iter().sum::<usize>().max(1) as f64;
+		let mut action_histogram = [0f64; Action::COUNT];
+		for (share, count) in action_histogram.iter_mut().zip(agent.action_counts.iter()) {
+			*share = *count as f64 / steps;
+		}
+
+		Self {
+			final_position: [
+				agent.position.x as f64 / size[0].max(1f64),
+				agent.position.y as f64 / size[1].max(1f64)
+			],
+			coverage: agent.visited.len() as f64 / (steps + 1f64),
+			action_histogram
+		}
+	}
+
+	/// Flattened in DIMENSION_NAMES order; every dimension lies in [0, 1].
+	pub fn descriptor(&self) -> Vec<f64> {
+		let mut descriptor = Vec::with_capacity(Behaviour::DIMENSIONS);
+		descriptor.extend_from_slice(&self.final_position);
+		descriptor.push(self.coverage);
+		descriptor.extend_from_slice(&self.action_histogram);
+		descriptor
+	}
+
+	/// Descriptors averaged dimension by dimension; all zeros when there are none.
+	pub fn mean_descriptor(behaviours: &[Behaviour]) -> Vec<f64> {
+		let mut mean = vec![0f64; Behaviour::DIMENSIONS];
+		for behaviour in behaviours {
+			for (total, value) in mean.iter_mut().zip(behaviour.descriptor()) {
+				*total += value;
+			}
+		}
+		for total in mean.iter_mut() {
+			*total /= behaviours.len().max(1) as f64;
+		}
+		mean
+	}
+}
+
+/// How novelty is scored, see NoveltyArchive; the `[evolution.novelty]` table.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NoveltyConfig {
+	/// how many nearest behaviours a descriptor's novelty is averaged over
+	pub neighbours: usize,
+	/// descriptors scoring above this are added to the archive
+	pub threshold: f64
+}
+
+impl Default for NoveltyConfig {
+	fn default() -> Self {
+		Self {
+			neighbours: 10,
+			threshold: 0.2
+		}
+	}
+}
+
+/// Behaviour descriptors found novel so far, which later descriptors are scored against.
+#[derive(Debug, Clone)]
+pub struct NoveltyArchive {
+	config: NoveltyConfig,
+	entries: Vec<Vec<f64>>
+}
+
+impl NoveltyArchive {
+	/// An empty archive.
+	pub fn new(config: NoveltyConfig) -> Self {
+		Self {
+			config,
+			entries: Vec::new()
+		}
+	}
+
+	/// Number of descriptors archived.
+	pub fn len(&self) -> usize {
+		self.entries.len()
+	}
+
+	/// Every descriptor archived, oldest first.
+	pub fn entries(&self) -> &[Vec<f64>] {
+		&self.entries
+	}
+
+	/// Replaces the archive's descriptors with `entries`, as saved in a Checkpoint.
+	pub fn restore(&mut self, entries: Vec<Vec<f64>>) {
+		self.entries = entries;
+	}
+
+	/// Whether nothing has been archived yet.
+	pub fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+
+	/// Scores each descriptor by its mean distance to the nearest other members of the population and
+	/// of the archive, then archives every descriptor that was novel enough.
+	pub fn score(&mut self, descriptors: &[Vec<f64>]) -> Vec<f64> {
+		let novelty = descriptors.iter()
+			.enumerate()
+			.map(|(idx, descriptor)| {
+				let mut distances = descriptors.iter()
+					.enumerate()
+					.filter(|(other_idx, _)| *other_idx != idx)
+					.map(|(_, other)| distance(descriptor, other))
+					.chain(self.entries.iter().map(|other| distance(descriptor, other)))
+					.collect::<Vec<f64>>();
+				distances.sort_by(|a, b| a.total_cmp(b));
+
+				let nearest = &distances[..self.config.neighbours.min(distances.len())];
+				if nearest.is_empty() {
+					0f64
+				} else {
+					nearest.iter().sum::<f64>() / nearest.len() as f64
+				}
+			})
+			.collect::<Vec<f64>>();
+
+		for (descriptor, score) in descriptors.iter().zip(novelty.iter()) {
+			if *score > self.config.threshold {
+				self.entries.push(descriptor.clone());
+			}
+		}
+
+		novelty
+	}
+}
+
+fn distance(a: &[f64], b: &[f64]) -> f64 {
+	a.iter()
+		.zip(b.iter())
+		.map(|(x, y)| (x - y).powi(2))
+		.sum::<f64>()
+		.sqrt()
+}
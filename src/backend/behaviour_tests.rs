@@ -0,0 +1,34 @@
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod behaviour_tests {
+	use crate::backend::agent::Agent;
+	use crate::backend::behaviour::{Behaviour, NoveltyArchive, NoveltyConfig};
+	use crate::backend::gene::Genome;
+	use crate::backend::map::{Action, Direction};
+	use crate::backend::Position;
+
+	#[test]
+	fn behaviour_from_moves() {
+		let mut agent = Agent::new(0, Position::new(5, 5), Genome::blank(0));
+		agent.record_move(&Action::Move(Direction::Right), Position::new(6, 5));
+		agent.record_move(&Action::Move(Direction::Left), Position::new(5, 5));
+
+		let behaviour = Behaviour::from_agent(&agent, [10f64, 10f64]);
+		assert_eq!(behaviour.final_position, [0.5, 0.5]);
+		assert_eq!(behaviour.action_histogram, [0.0, 0.0, 0.5, 0.5, 0.0]);
+		assert!((behaviour.coverage - 2.0 / 3.0).abs() < 1e-9);
+		assert_eq!(behaviour.descriptor().len(), Behaviour::DIMENSIONS);
+	}
+
+	#[test]
+	fn novelty_prefers_outliers() {
+		let mut archive = NoveltyArchive::new(NoveltyConfig { neighbours: 2, threshold: 0.5 });
+		let descriptors = vec![vec![0.0, 0.0], vec![0.1, 0.0], vec![0.0, 0.1], vec![1.0, 1.0]];
+
+		let novelty = archive.score(&descriptors);
+		assert!(novelty[3] > novelty[0]);
+		assert!(novelty[3] > novelty[1]);
+		// only the outlier clears the threshold
+		assert_eq!(archive.len(), 1);
+	}
+}
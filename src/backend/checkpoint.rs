@@ -0,0 +1,43 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use crate::backend::archive::GenomeRecord;
+use crate::backend::config::ExperimentConfig;
+use crate::backend::error::Result;
+use crate::backend::lineage::LineageNode;
+
+/// Everything needed to carry on an evolution run where it stopped, see Orchestrator::checkpoint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checkpoint {
+	/// the experiment being run, so `resume` needs nothing else
+	pub experiment: ExperimentConfig,
+	/// generations completed so far, and so the next generation to run
+	pub generation: usize,
+	/// the last generation's genome ids
+	pub population: Vec<u64>,
+	/// every genome the pool held in memory
+	pub genomes: Vec<GenomeRecord>,
+	/// see Optimizer::state
+	pub optimizer: serde_json::Value,
+	/// behaviour descriptors archived so far, for novelty fitness
+	pub novelty_archive: Vec<Vec<f64>>,
+	/// every genome evaluated so far and its parents
+	pub lineage: Vec<LineageNode>,
+	/// the run's random generator is reseeded with this, so resuming continues the same sequence
+	pub rng_seed: u64
+}
+
+impl Checkpoint {
+	/// Writes the checkpoint to `path` as JSON.
+	pub fn save(&self, path: &Path) -> Result<()> {
+		let mut writer = BufWriter::new(File::create(path)?);
+		serde_json::to_writer(&mut writer, self)?;
+		Ok(writer.flush()?)
+	}
+
+	/// Reads a checkpoint written by `save`.
+	pub fn load(path: &Path) -> Result<Self> {
+		Ok(serde_json::from_reader(BufReader::new(File::open(path)?))?)
+	}
+}
@@ -0,0 +1,104 @@
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod checkpoint_tests {
+	use std::collections::HashMap;
+	use crate::backend::checkpoint::Checkpoint;
+	use crate::backend::config::ExperimentConfig;
+	use crate::backend::engine::EngineConfig;
+	use crate::backend::evaluation::Evaluation;
+	use crate::backend::random;
+	use crate::backend::Orchestrator;
+
+	fn experiment() -> ExperimentConfig {
+		toml::from_str("
+			generations = 3
+
+			[engine]
+			size = [20.0, 20.0]
+			round_max = 30
+			agent_count = 6
+
+			[evolution]
+			trials = 2
+			threads = 2
+		").unwrap()
+	}
+
+	fn fitness(evaluations: &HashMap<u64, Evaluation>) -> Vec<(u64, f64)> {
+		let mut fitness = evaluations.iter()
+			.map(|(id, evaluation)| (*id, evaluation.fitness))
+			.collect::<Vec<(u64, f64)>>();
+		fitness.sort_by_key(|(id, _)| *id);
+		fitness
+	}
+
+	#[test]
+	fn parses_partial_config() {
+		let experiment = experiment();
+		assert_eq!(experiment.engine.agent_count, 6);
+		assert_eq!(experiment.engine.food_spread, EngineConfig::default().food_spread);
+		assert_eq!(experiment.checkpoint_interval, ExperimentConfig::default().checkpoint_interval);
+		assert!(toml::from_str::<ExperimentConfig>("generation = 3").is_err());
+	}
+
+	#[test]
+	fn resolved_config_roundtrips() {
+		let mut experiment: ExperimentConfig = toml::from_str("
+			[engine]
+			vision_radius = 2
+			food_rate = 0.5
+
+			[evolution]
+			hidden_nodes = 16
+			optimizer = { type = \"cma_es\", sigma = 0.1 }
+			training_policy = { type = \"epsilon_greedy\", epsilon = 0.1 }
+			mutation = [{ type = \"gaussian\", sigma = 0.05, probability = 0.5 }]
+		").unwrap();
+		experiment.validate().unwrap();
+
+		let path = std::env::temp_dir().join(format!("cadmus_config_{}.toml", std::process::id()));
+		experiment.save(&path).unwrap();
+		let loaded = ExperimentConfig::load(&path).unwrap();
+		std::fs::remove_file(&path).unwrap();
+		assert_eq!(loaded, experiment);
+
+		experiment.engine.agent_count = 0;
+		experiment.engine.food_spread = 0f64;
+		let error = experiment.validate().unwrap_err().to_string();
+		assert!(error.contains("agent_count") && error.contains("food_spread"));
+	}
+
+	#[test]
+	fn checkpointing_leaves_the_run_unchanged() {
+		let experiment = experiment();
+		let mut runs = Vec::new();
+		for checkpointed in [false, true] {
+			random::reseed(7);
+			let mut orchestrator = Orchestrator::with_config(experiment.engine.clone(), experiment.orchestrator_config()).unwrap();
+			let first = orchestrator.run_generation(0).unwrap();
+			if checkpointed {
+				orchestrator.checkpoint(&experiment, 1, &Orchestrator::population(&first));
+			}
+			runs.push(fitness(&orchestrator.run_generation(1).unwrap()));
+		}
+		assert_eq!(runs[0], runs[1]);
+	}
+
+	#[test]
+	fn resumed_run_matches_uninterrupted_run() {
+		let experiment = experiment();
+
+		random::reseed(11);
+		let mut uninterrupted = Orchestrator::with_config(experiment.engine.clone(), experiment.orchestrator_config()).unwrap();
+		let first = uninterrupted.run_generation(0).unwrap();
+		let checkpoint = uninterrupted.checkpoint(&experiment, 1, &Orchestrator::population(&first));
+		let expected = uninterrupted.run_generation(1).unwrap();
+
+		let serialised = serde_json::to_string(&checkpoint).unwrap();
+		let checkpoint: Checkpoint = serde_json::from_str(&serialised).unwrap();
+		let mut resumed = Orchestrator::resume(checkpoint).unwrap();
+		let actual = resumed.run_generation(1).unwrap();
+
+		assert_eq!(fitness(&actual), fitness(&expected));
+	}
+}
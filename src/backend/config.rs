@@ -0,0 +1,164 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use crate::backend::behaviour::Behaviour;
+use crate::backend::engine::EngineConfig;
+use crate::backend::error::{Problems, Result};
+use crate::backend::evaluation::FitnessMode;
+use crate::backend::island::IslandConfig;
+use crate::backend::mutation::MutationOperator;
+use crate::backend::network::{NetworkShape, OUTPUT_NODES};
+use crate::backend::optimizer::OptimizerKind;
+use crate::backend::policy::PolicyMode;
+use crate::backend::OrchestratorConfig;
+
+/// A complete experiment as described by a config file, TOML unless the file ends in .json. Every
+/// field is optional in the file and falls back to its default; the world is set up by the
+/// `[engine]` table, selection by the `[evolution]` table, and an `[islands]` table splits the
+/// population into islands.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ExperimentConfig {
+	/// generations to run
+	pub generations: usize,
+	/// seeds every random draw of the run when set
+	pub seed: Option<u64>,
+	/// generations between checkpoints written to output_dir
+	pub checkpoint_interval: usize,
+	/// where metrics, checkpoints and the run's artefacts are written; nothing is written when unset
+	pub output_dir: Option<PathBuf>,
+	/// the `[engine]` table
+	pub engine: EngineConfig,
+	/// the `[evolution]` table
+	pub evolution: OrchestratorConfig,
+	/// splits the population into an Archipelago of migrating islands when set
+	pub islands: Option<IslandConfig>
+}
+
+impl Default for ExperimentConfig {
+	fn default() -> Self {
+		Self {
+			generations: 100,
+			seed: None,
+			checkpoint_interval: 10,
+			output_dir: None,
+			engine: EngineConfig::default(),
+			evolution: OrchestratorConfig::default(),
+			islands: None
+		}
+	}
+}
+
+fn is_json(path: &Path) -> bool {
+	path.extension().is_some_and(|extension| extension == "json")
+}
+
+impl ExperimentConfig {
+	/// Reads and validates an experiment.
+	pub fn load(path: &Path) -> Result<Self> {
+		let contents = fs::read_to_string(path)?;
+		let experiment: ExperimentConfig = if is_json(path) {
+			serde_json::from_str(&contents)?
+		} else {
+			toml::from_str(&contents)?
+		};
+		experiment.validate()?;
+		Ok(experiment)
+	}
+
+	/// Writes the config with every default filled in, in the same format `load` reads from `path`.
+	pub fn save(&self, path: &Path) -> Result<()> {
+		let contents = if is_json(path) {
+			serde_json::to_string_pretty(self)?
+		} else {
+			toml::to_string(self)?
+		};
+		Ok(fs::write(path, contents)?)
+	}
+
+	/// Rejects settings the run would fail or hang on, naming every offending field.
+	pub fn validate(&self) -> Result<()> {
+		let mut problems = Problems::default();
+		problems.check(self.generations > 0, "generations must be at least 1");
+		problems.check(self.checkpoint_interval > 0, "checkpoint_interval must be at least 1");
+		problems.merge(self.engine.validate());
+		problems.merge(self.evolution.validate(&self.engine));
+		if let Some(islands) = &self.islands {
+			problems.merge(islands.validate(&self.engine, &self.evolution));
+		}
+		problems.into_result()
+	}
+
+	/// The evolution settings, writing into this experiment's output_dir.
+	pub fn orchestrator_config(&self) -> OrchestratorConfig {
+		OrchestratorConfig {
+			output_dir: self.output_dir.clone(),
+			..self.evolution.clone()
+		}
+	}
+}
+
+impl OrchestratorConfig {
+	/// Checks the settings against the engine the population will play in, see ExperimentConfig::validate.
+	pub fn validate(&self, engine: &EngineConfig) -> Result<()> {
+		let mut problems = Problems::default();
+		problems.check(engine.agent_count > 0, "engine.agent_count must be at least 1");
+		problems.check(self.trials > 0, "evolution.trials must be at least 1");
+		problems.check(self.threads > 0, "evolution.threads must be at least 1");
+		problems.check(self.hidden_nodes > 0, "evolution.hidden_nodes must be at least 1");
+		// an oversized vision is reported by EngineConfig::validate and would overflow the count here
+		if engine.vision_radius <= EngineConfig::MAX_VISION_RADIUS {
+			let weights = (engine.vision_tiles() + OUTPUT_NODES + 1).saturating_mul(self.hidden_nodes).saturating_add(OUTPUT_NODES);
+			problems.check(weights <= NetworkShape::MAX_WEIGHTS, &format!("evolution.hidden_nodes must keep the network within {} weights", NetworkShape::MAX_WEIGHTS));
+		}
+		problems.check(self.species_threshold >= 0f64, "evolution.species_threshold must not be negative");
+		problems.check(self.novelty.neighbours > 0, "evolution.novelty.neighbours must be at least 1");
+		problems.check(engine.agent_count + self.baselines.len() <= engine.tiles(), "evolution.baselines and engine.agent_count must fit on the map together");
+		for policy in [self.training_policy, self.evaluation_policy] {
+			match policy {
+				PolicyMode::EpsilonGreedy { epsilon } => problems.check((0f64..=1f64).contains(&epsilon), "policy epsilon must lie in [0, 1]"),
+				PolicyMode::Temperature { temperature } => problems.check(temperature >= 0f64, "policy temperature must not be negative"),
+				PolicyMode::Stochastic | PolicyMode::Greedy => {}
+			}
+		}
+		if let FitnessMode::Combined { novelty_weight } = self.fitness_mode {
+			problems.check((0f64..=1f64).contains(&novelty_weight), "evolution.fitness_mode.novelty_weight must lie in [0, 1]");
+		}
+
+		for operator in &self.mutation {
+			match *operator {
+				MutationOperator::Gaussian { sigma, probability } => {
+					problems.check(sigma >= 0f32, "gaussian mutation sigma must not be negative");
+					problems.check((0f64..=1f64).contains(&probability), "mutation probabilities must lie in [0, 1]");
+				}
+				MutationOperator::Reset { range, probability } => {
+					problems.check(range > 0f32, "reset mutation range must be positive");
+					problems.check((0f64..=1f64).contains(&probability), "mutation probabilities must lie in [0, 1]");
+				}
+				MutationOperator::SignFlip { probability } => {
+					problems.check((0f64..=1f64).contains(&probability), "mutation probabilities must lie in [0, 1]");
+				}
+				MutationOperator::Clip { min, max } => problems.check(min <= max, "clip mutation min must not exceed max"),
+				MutationOperator::SelfAdaptive { min_step } => problems.check(min_step >= 0f32, "self-adaptive mutation min_step must not be negative")
+			}
+		}
+
+		match &self.optimizer {
+			OptimizerKind::Genetic { elites, randoms } => {
+				problems.check(*elites > 0, "evolution.optimizer.elites must be at least 1");
+				problems.check(elites + randoms <= engine.agent_count, "evolution.optimizer elites and randoms must fit in engine.agent_count");
+			}
+			OptimizerKind::CmaEs { sigma } => problems.check(*sigma > 0f64, "evolution.optimizer.sigma must be positive"),
+			OptimizerKind::NaturalEs { sigma, learning_rate } => {
+				problems.check(*sigma > 0f64, "evolution.optimizer.sigma must be positive");
+				problems.check(*learning_rate > 0f64, "evolution.optimizer.learning_rate must be positive");
+			}
+			OptimizerKind::MapElites { dimensions, bins } => {
+				problems.check(!dimensions.is_empty(), "evolution.optimizer.dimensions must not be empty");
+				problems.check(dimensions.iter().all(|dimension| *dimension < Behaviour::DIMENSIONS), "evolution.optimizer.dimensions must index Behaviour::DIMENSION_NAMES");
+				problems.check(*bins > 0, "evolution.optimizer.bins must be at least 1");
+			}
+		}
+		problems.into_result()
+	}
+}
@@ -1,88 +1,230 @@
 use std::collections::{HashMap, HashSet};
-use std::hash::Hash;
-use std::ops::Index;
-use poisson_diskus::bridson;
+use serde::{Deserialize, Serialize};
 use crate::backend::agent::{Agent, AgentSense, AgentStats};
+use crate::backend::behaviour::Behaviour;
 use crate::backend::entity::{Entity, EntityType};
-use crate::backend::gene::{Genome, GenomePool};
+use crate::backend::error::{Error, Problems, Result};
+use crate::backend::gene::Genome;
 use crate::backend::map::{Action, Direction};
+use crate::backend::policy::PolicyMode;
+use crate::backend::random::rng;
+use crate::backend::sampling::poisson_disc;
+use crate::backend::replay::Replay;
 use crate::backend::{Offset, Position};
-use std::rc::Rc;
-use rand::prelude::{IteratorRandom, SliceRandom};
+use rand::Rng;
+use rand::prelude::SliceRandom;
 
+/// Everything one match produced, keyed by genome id.
 #[derive(Debug)]
-pub struct MatchStats {
+pub struct MatchResult {
+	/// each agent's stats for the match
 	pub agent_stats: HashMap<u64, AgentStats>,
+	/// each agent's behaviour over the match
+	pub behaviours: HashMap<u64, Behaviour>,
+	/// rounds played
 	pub duration: usize,
 }
 
+/// The simulated world: a rectangular map of food and agents, played one match at a time.
+/// Matches are begun with `begin_match` and advanced with `step`, or played out with `play_match`.
 #[derive(Debug)]
 pub struct Engine {
+	/// the world matches are played in
 	pub config: EngineConfig,
+	/// rounds played so far in the current match
 	pub round_idx: usize,
 	// pub entities: Vec<Entity>,
+	/// food on the map, by tile
 	pub entities: HashMap<Position, Entity>,
+	/// agents in the current match, by id
 	pub agents: HashMap<u64, Agent>,
+	/// set once the current match has played `round_max` rounds
 	pub game_concluded: bool,
-	pub round: usize
+	/// the generation the current match belongs to, as passed to `begin_match`
+	pub round: usize,
+	/// how agents pick actions from their networks' outputs
+	pub policy: PolicyMode,
+	/// when set, every match is traced into `replay`
+	pub record_replay: bool,
+	/// the trace of the current or last match, while `record_replay` is set
+	pub replay: Option<Replay>,
+	// fractional food carried over between rounds, see EngineConfig::food_rate
+	food_budget: f64
 }
 
-#[derive(Debug)]
+/// The world an Engine simulates. Every field has a default, so configs can be built with
+/// `..EngineConfig::default()` and are checked by `validate`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct EngineConfig {
+	/// the map's extent; tiles run from 0 to size inclusive on each axis
 	pub size: [f64; 2],
+	/// rounds each match lasts
 	pub round_max: usize,
+	/// minimum distance between the food placed at the start of a match
 	pub food_spread: f64,
+	/// agents per match, and so the population size
 	pub agent_count: usize,
+	/// food placed on random empty tiles every round, on average; none respawns at 0
+	pub food_rate: f64,
+	/// how many tiles an agent sees in each direction
+	pub vision_radius: usize
+}
+
+impl Default for EngineConfig {
+	fn default() -> Self {
+		Self {
+			food_spread: 2.5f64,
+			size: [100f64, 100f64],
+			round_max: 500,
+			agent_count: 10,
+			food_rate: 0f64,
+			vision_radius: EngineConfig::DEFAULT_VISION_RADIUS
+		}
+	}
+}
+
+impl EngineConfig {
+	/// the vision_radius used when a config doesn't set one
+	pub const DEFAULT_VISION_RADIUS: usize = 3;
+	/// the largest side `validate` accepts, which keeps tile counts and food placement tractable
+	pub const MAX_SIZE: f64 = 1000f64;
+	/// the closest food spacing `validate` accepts; food lands on whole tiles, so closer adds nothing
+	pub const MIN_FOOD_SPREAD: f64 = 1f64;
+	/// the widest vision `validate` accepts; every seen tile is a network input
+	pub const MAX_VISION_RADIUS: usize = 32;
+
+	/// side length of the square an agent sees, centred on itself
+	pub fn vision_length(&self) -> usize {
+		self.vision_radius * 2 + 1
+	}
+
+	/// tiles in an AgentSense, which is also the input size of the agents' networks
+	pub fn vision_tiles(&self) -> usize {
+		self.vision_length().pow(2)
+	}
+
+	/// tiles agents can stand on; positions run from 0 to `size` inclusive
+	pub fn tiles(&self) -> usize {
+		self.size.iter()
+			.map(|side| (side.max(0f64) as usize).saturating_add(1))
+			.fold(1, usize::saturating_mul)
+	}
+
+	/// Rejects worlds the engine can't play, naming every offending field.
+	pub fn validate(&self) -> Result<()> {
+		let mut problems = Problems::default();
+		problems.check(self.size.iter().all(|side| (1f64..=EngineConfig::MAX_SIZE).contains(side)), &format!("engine.size must lie between 1 and {} in both dimensions", EngineConfig::MAX_SIZE));
+		problems.check(self.agent_count <= self.tiles(), "engine.agent_count must not exceed the number of tiles");
+		problems.check(self.food_spread >= EngineConfig::MIN_FOOD_SPREAD && self.food_spread.is_finite(), &format!("engine.food_spread must be a finite number of at least {}", EngineConfig::MIN_FOOD_SPREAD));
+		problems.check(self.food_rate >= 0f64 && self.food_rate <= self.tiles() as f64, "engine.food_rate must lie between 0 and the number of tiles");
+		problems.check(self.vision_radius <= EngineConfig::MAX_VISION_RADIUS, &format!("engine.vision_radius must not exceed {}", EngineConfig::MAX_VISION_RADIUS));
+		problems.into_result()
+	}
+
+	/// `count` distinct random tiles to start agents on.
+	pub fn spawn_positions(&self, count: usize) -> Result<Vec<Position>> {
+		let tiles = self.tiles();
+		if count > tiles {
+			return Err(Error::MapFull { agents: count, tiles });
+		}
+
+		let mut rng = rng();
+		let mut seen_positions = HashSet::new();
+		let mut positions = Vec::with_capacity(count);
+		while positions.len() < count {
+			let position = Position::new(
+				rng.gen_range(0..=(self.size[0] as i32)),
+				rng.gen_range(0..=(self.size[1] as i32))
+			);
+			if seen_positions.insert(position) {
+				positions.push(position);
+			}
+		}
+		Ok(positions)
+	}
 }
 
 impl Engine {
-	const FOOD_RATE: f32 = 5.0;
 
-	pub fn new(config: EngineConfig) -> Self {
-		let mut engine = Self {
+	/// Fails if the config can't be played, see EngineConfig::validate.
+	pub fn new(config: EngineConfig) -> Result<Self> {
+		config.validate()?;
+		Ok(Self {
 			config,
 			round_idx: 0,
 			// entities: Vec::new(),
 			agents: HashMap::new(),
 			game_concluded: false,
 			entities: HashMap::new(),
-			round: 0
-		};
-		engine
+			round: 0,
+			policy: PolicyMode::Stochastic,
+			record_replay: false,
+			replay: None,
+			food_budget: 0f64
+		})
 	}
 
+	/// Plays a match between `agents`, keyed by id, to the end and returns its results.
+	pub fn play_match(&mut self, agents: HashMap<u64, Agent>, round: usize) -> Result<MatchResult> {
+		self.begin_match(agents, round);
+		while !self.game_concluded {
+			self.step()?;
+		}
+		Ok(self.match_result())
+	}
 
-	pub fn play_match(&mut self, agents: HashMap<u64, Agent>, round: usize) -> MatchStats {
+	/// Sets up a new match without playing it, for callers that want to drive it with `step`.
+	pub fn begin_match(&mut self, agents: HashMap<u64, Agent>, round: usize) {
 		self.reset();
 		self.agents = agents;
 		self.initialise();
 		self.round = round;
-
-		while !self.game_concluded {
-			self.step()
+		if self.record_replay {
+			self.replay = Some(Replay::begin(self));
 		}
+	}
 
-		let stats = MatchStats {
+	/// The results of the match so far.
+	pub fn match_result(&self) -> MatchResult {
+		MatchResult {
 			agent_stats: self.agents.iter()
-				.map(|(idx, x)| (*idx, x.genome.borrow().stats.clone()))
+				.map(|(idx, x)| (*idx, x.stats))
 				.collect::<HashMap<u64, AgentStats>>(),
-			duration: self.round_idx.clone(),
-		};
+			behaviours: self.agents.iter()
+				.map(|(idx, x)| (*idx, Behaviour::from_agent(x, self.config.size)))
+				.collect::<HashMap<u64, Behaviour>>(),
+			duration: self.round_idx,
+		}
+	}
 
-		stats
+	/// Plays one round; `game_concluded` is set once the match is over.
+	pub fn step(&mut self) -> Result<()> {
+		let actions = self.process_agents()?;
+		self.step_with(actions)?;
+		Ok(())
 	}
 
-	fn step(&mut self) {
-		if self.round_idx > self.config.round_max {
-			self.game_concluded = true;
+	/// Plays one round in which every agent in `actions` takes its action and the rest stay put,
+	/// for actions chosen outside the engine. Returns which agent ate food where.
+	pub fn step_with(&mut self, actions: HashMap<u64, Action>) -> Result<Vec<(u64, Position)>> {
+		let recorded_actions = self.replay.as_ref().map(|_| actions.clone());
+		let food_eaten = self.apply_actions(actions)?;
+		let food_spawned = self.spawn_food();
+		if let Some(recorded_actions) = recorded_actions {
+			let mut replay = self.replay.take().unwrap();
+			replay.record_step(self, &recorded_actions, &food_eaten, &food_spawned);
+			self.replay = Some(replay);
 		}
 
-		let actions = self.process_agents();
-		self.apply_actions(actions);
-
 		self.round_idx += 1;
+		if self.round_idx >= self.config.round_max {
+			self.game_concluded = true;
+		}
+		Ok(food_eaten)
 	}
 
+	/// The tile `action` would take `agent` to, ignoring other agents.
 	pub fn resolve_action(&self, agent: &Agent, action: &Action) -> Position {
 		let current_pos = agent.position;
 		let mut target_pos = match action {
@@ -96,8 +238,8 @@ impl Engine {
 			}
 			Action::Reproduce => { current_pos }
 		};
-		let bounds = (*self.config.size.get(0).unwrap() as i32,
-					  *self.config.size.get(1).unwrap() as i32);
+		let bounds = (self.config.size[0] as i32,
+					  self.config.size[1] as i32);
 
 		if target_pos.x > bounds.0 {
 			target_pos.x = bounds.0;
@@ -116,16 +258,16 @@ impl Engine {
 	}
 
 	fn resolve_target_position(&self, seen_positions: &mut HashSet<Position>, agent: &Agent, action: &Action) -> Position {
-		let target_position = self.resolve_action(agent, &action);
+		let target_position = self.resolve_action(agent, action);
 
 		let duplicated_position = !seen_positions.insert(target_position);
-		return if duplicated_position {
-			let possible_directions = match action {
+		if duplicated_position {
+			let mut possible_directions = match action {
 				Action::Move(dir) => {
 					[Direction::Up, Direction::Down, Direction::Left, Direction::Right]
 						.iter()
 						.filter(|a| **a != *dir)
-						.map(|a| *a)
+						.copied()
 						.collect()
 				}
 				Action::Reproduce => {
@@ -133,24 +275,37 @@ impl Engine {
 						.to_vec()
 				}
 			};
-			let new_direction = possible_directions
-				.choose(&mut rand::thread_rng())
-				.unwrap()
-				.clone();
-			let new_action = Action::Move(new_direction);
-			let new_position = self.resolve_target_position(seen_positions, agent, &new_action);
-
-			new_position
+			possible_directions.shuffle(&mut rng());
+
+			for new_direction in possible_directions {
+				let new_position = self.resolve_action(agent, &Action::Move(new_direction));
+				if seen_positions.insert(new_position) {
+					return new_position;
+				}
+			}
+
+			// boxed in on every side, so stay put rather than search forever
+			seen_positions.insert(agent.position);
+			agent.position
 		} else {
 			target_position
 		}
 	}
 
-	pub fn apply_actions(&mut self, actions: HashMap<u64, Action>) {
-		let mut seen_positions: HashSet<Position> = HashSet::new();
+	/// Moves every agent and lets it eat; returns which agent ate food where.
+	pub fn apply_actions(&mut self, actions: HashMap<u64, Action>) -> Result<Vec<(u64, Position)>> {
+		// agents without an action stay put, so nobody may move onto their tiles
+		let mut seen_positions = self.agents.values()
+			.filter(|agent| !actions.contains_key(&agent.id))
+			.map(|agent| agent.position)
+			.collect::<HashSet<Position>>();
 		let mut target_positions: HashMap<u64, Position> = HashMap::new();
-		for (idx, action) in actions.iter() {
-			let agent: &Agent = self.agents.get(idx).unwrap();
+		// resolve in id order so collisions play out the same way for the same seed
+		let mut ids = actions.keys().cloned().collect::<Vec<u64>>();
+		ids.sort();
+		for idx in &ids {
+			let action = &actions[idx];
+			let agent: &Agent = self.agents.get(idx).ok_or(Error::UnknownAgent(*idx))?;
 
 			let new_position = self.resolve_target_position(&mut seen_positions, agent, action);
 			target_positions.insert(*idx, new_position);
@@ -158,42 +313,74 @@ impl Engine {
 
 		// set new position
 		for (idx, target) in target_positions.iter() {
-			self.agents.get_mut(idx).unwrap().position = *target
+			let agent = self.agents.get_mut(idx).unwrap();
+			agent.stats.steps_taken += 1;
+			agent.record_move(&actions[idx], *target);
 		}
 
 		// consume food
-		for (idx, agent) in self.agents.iter_mut() {
+		let mut food_eaten = Vec::new();
+		for agent in self.agents.values_mut() {
 			if self.entities.contains_key(&agent.position) {
 				self.entities.remove(&agent.position);
 				agent.increment_food();
+				food_eaten.push((agent.id, agent.position));
 			}
 		}
+		Ok(food_eaten)
 	}
 
-	pub fn process_agents(&mut self) -> HashMap<u64, Action> {
-		let mut actions: HashMap<u64, Action> = HashMap::new();
+	/// Every agent's next action. Genome-controlled agents are evaluated in one batched forward pass;
+	/// scripted ones ask their own policy.
+	pub fn process_agents(&mut self) -> Result<HashMap<u64, Action>> {
 		self.collect_visions();
-		for (idx, agent) in self.agents.iter_mut() {
-			let action = agent.get_action();
-			actions.insert(agent.id, action);
+		let mut ids = self.agents.keys().cloned().collect::<Vec<u64>>();
+		ids.sort();
+		let (evolved, scripted): (Vec<u64>, Vec<u64>) = ids.into_iter()
+			.partition(|id| self.agents[id].policy.is_none() && self.agents[id].genome.is_some());
+
+		let mut actions = HashMap::with_capacity(self.agents.len());
+		if !evolved.is_empty() {
+			let mut inputs = Vec::with_capacity(evolved.len() * self.config.vision_tiles());
+			for id in &evolved {
+				inputs.extend(self.agents[id].build_input()?);
+			}
+
+			let genomes = evolved.iter()
+				.filter_map(|id| self.agents[id].genome.as_ref())
+				.collect::<Vec<&Genome>>();
+			let action_indices = Genome::forward_batch(&genomes, &inputs, self.policy);
+			actions.extend(evolved.into_iter()
+				.zip(action_indices)
+				.map(|(id, action_idx)| (id, Action::from_index(action_idx))));
 		}
-		actions
+		for id in scripted {
+			actions.insert(id, self.agents[&id].get_action(self.policy)?);
+		}
+		Ok(actions)
 	}
 
-	pub const DISTANCE_VISIBLE_SIDE : i32 = 3;
-	pub const DISTANCE_VISIBLE_LENGTH : usize = (Engine::DISTANCE_VISIBLE_SIDE as usize * 2) + 1;
-	pub const DISTANCE_VISIBLE_BLOCKS : usize = (Engine::DISTANCE_VISIBLE_LENGTH as usize).pow(2);
+	/// What every agent currently sees.
+	pub fn observe(&mut self) -> HashMap<u64, AgentSense> {
+		self.collect_visions();
+		self.agents.iter()
+			.map(|(id, agent)| (*id, agent.current_sense.clone().unwrap()))
+			.collect()
+	}
 
+	/// Updates every agent's current sense from the food around it.
 	pub fn collect_visions(&mut self) {
-		for (idx, agent) in &mut self.agents {
+		let radius = self.config.vision_radius as i32;
+		let length = self.config.vision_length();
+		for agent in self.agents.values_mut() {
 			let mut agent_sense = AgentSense {
 				position: agent.position,
-				map_tiles: [false; Engine::DISTANCE_VISIBLE_BLOCKS]
+				map_tiles: vec![false; self.config.vision_tiles()]
 			};
-			for (x_idx,x) in (-Engine::DISTANCE_VISIBLE_SIDE..Engine::DISTANCE_VISIBLE_SIDE).enumerate() {
-				for (y_idx, y) in (-Engine::DISTANCE_VISIBLE_SIDE..Engine::DISTANCE_VISIBLE_SIDE).enumerate() {
-					let target = agent.position + Position::new(x as i32, y as i32);
-					let tile_index = x_idx + (y_idx * Engine::DISTANCE_VISIBLE_LENGTH);
+			for (x_idx,x) in (-radius..=radius).enumerate() {
+				for (y_idx, y) in (-radius..=radius).enumerate() {
+					let target = agent.position + Position::new(x, y);
+					let tile_index = x_idx + (y_idx * length);
 					if self.entities.contains_key(&target) {
 						agent_sense.map_tiles[tile_index] = true;
 					}
@@ -203,30 +390,28 @@ impl Engine {
 		}
 	}
 
+	/// Clears the map and agents, ready for the next match.
 	pub fn reset(&mut self) {
 		self.round_idx = 0;
 		// self.entities = HashMap::new();
 		self.entities = HashMap::new();
 		self.agents = HashMap::new();
 		self.game_concluded = false;
+		self.replay = None;
+		self.food_budget = 0f64;
 	}
 
 	pub(crate) fn initialise(&mut self) {
 		self.place_food();
-
-
-		// self.place_agents()
 	}
 
 	fn place_food(&mut self) {
 		let rmin = self.config.food_spread;
 		let k = 10;
-		let use_pbc = false;
 
-		let coords: Vec<Position> = bridson(&self.config.size, rmin, k, use_pbc)
-			.unwrap()
+		let coords: Vec<Position> = poisson_disc(self.config.size, rmin, k, &mut rng())
 			.iter().map(|a| a.map(|x| x as i32))
-			.map(|a| Position::from(a))
+			.map(Position::from)
 			.collect();
 
 		// println!("{:?}", coords);
@@ -237,5 +422,30 @@ impl Engine {
 		}
 	}
 
-	fn place_agents(&mut self) {}
+	// Places this round's share of `food_rate` on random tiles holding neither food nor an agent.
+	fn spawn_food(&mut self) -> Vec<Position> {
+		self.food_budget += self.config.food_rate;
+		let occupied = self.agents.values()
+			.map(|agent| agent.position)
+			.collect::<HashSet<Position>>();
+
+		let mut rng = rng();
+		let mut spawned = Vec::new();
+		while self.food_budget >= 1f64 {
+			self.food_budget -= 1f64;
+			// give up on this one rather than search a nearly full map forever
+			for _ in 0..10 {
+				let position = Position::new(
+					rng.gen_range(0..=self.config.size[0] as i32),
+					rng.gen_range(0..=self.config.size[1] as i32)
+				);
+				if !occupied.contains(&position) && !self.entities.contains_key(&position) {
+					self.entities.insert(position, Entity::new(position, EntityType::Food));
+					spawned.push(position);
+					break;
+				}
+			}
+		}
+		spawned
+	}
 }
\ No newline at end of file
@@ -1,35 +1,33 @@
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod engine_tests {
-	use std::cell::RefCell;
 	use std::collections::{HashMap, HashSet};
-	use std::rc::Rc;
-	use crate::backend::agent::{Agent, AgentStats};
+	use crate::backend::agent::Agent;
 	use crate::backend::engine::{Engine, EngineConfig};
+	use crate::backend::error::Error;
 	use crate::backend::gene::Genome;
 	use crate::backend::map::{Action, Direction};
-	use crate::backend::Position;
+	use crate::backend::network::NetworkShape;
+	use crate::backend::random;
+	use crate::backend::{Orchestrator, OrchestratorConfig, Position};
 
 	fn get_engine() -> Engine {
 		let engine_config = EngineConfig {
 			food_spread: 2.5f64,
 			size: [100f64, 100f64],
 			round_max: 1000,
-			agent_count: 10
+			agent_count: 10,
+			..EngineConfig::default()
 		};
-		let mut engine = Engine::new(engine_config);
+		let mut engine = Engine::new(engine_config).unwrap();
 		engine.reset();
 		engine
 	}
 
 	#[test]
 	fn move_normal() {
-		let mut engine = get_engine();
-		let agent1 = Agent {
-			position: Position::new(5,5),
-			id: 0,
-			genome: Rc::new(RefCell::new(Genome::blank(1))),
-			current_sense: None
-		};
+		let engine = get_engine();
+		let agent1 = Agent::new(0, Position::new(5,5), Genome::blank(1));
 		let action = Action::Move(Direction::Up);
 		let target_position = engine.resolve_action(&agent1, &action);
 		assert_eq!(target_position, Position::new(5, 4));
@@ -50,18 +48,8 @@ mod engine_tests {
 	#[test]
 	fn move_collision() {
 		let mut engine = get_engine();
-		let agent1 = Agent {
-			position: Position::new(5,4),
-			id: 1,
-			genome: Rc::new(RefCell::new(Genome::blank(1))),
-			current_sense: None
-		};
-		let agent2 = Agent {
-			position: Position::new(5,6),
-			genome: Rc::new(RefCell::new(Genome::blank(2))),
-			id: 2,
-			current_sense: None
-		};
+		let agent1 = Agent::new(1, Position::new(5,4), Genome::blank(1));
+		let agent2 = Agent::new(2, Position::new(5,6), Genome::blank(2));
 
 		engine.agents.insert(1, agent1);
 		engine.agents.insert(2, agent2);
@@ -73,24 +61,31 @@ mod engine_tests {
 		actions.insert(1, action1);
 		actions.insert(2, action2);
 
-		engine.apply_actions(actions);
+		engine.apply_actions(actions).unwrap();
 
 		let mut seen_positions: HashSet<Position> = HashSet::new();
-		for (idx, agent) in engine.agents.iter() {
-			seen_positions.insert(agent.position.clone());
+		for agent in engine.agents.values() {
+			seen_positions.insert(agent.position);
 		}
 		assert_eq!(seen_positions.len(), 2);
 	}
 
 	#[test]
-	fn move_oob() {
+	fn move_around_idle_agent() {
 		let mut engine = get_engine();
-		let mut agent1 = Agent {
-			position: Position::new(0,0),
-			id: 0,
-			genome: Rc::new(RefCell::new(Genome::blank(0))),
-			current_sense: None
-		};
+		engine.agents.insert(1, Agent::new(1, Position::new(5, 5), Genome::blank(1)));
+		engine.agents.insert(2, Agent::new(2, Position::new(4, 5), Genome::blank(2)));
+
+		// agent 1 has no action and stays put, so agent 2 must step somewhere else
+		engine.apply_actions(HashMap::from([(2, Action::Move(Direction::Right))])).unwrap();
+		assert_eq!(engine.agents[&1].position, Position::new(5, 5));
+		assert_ne!(engine.agents[&2].position, Position::new(5, 5));
+	}
+
+	#[test]
+	fn move_oob() {
+		let engine = get_engine();
+		let mut agent1 = Agent::new(0, Position::new(0,0), Genome::blank(0));
 
 		let action = Action::Move(Direction::Up);
 		let target_position = engine.resolve_action(&agent1, &action);
@@ -117,16 +112,86 @@ mod engine_tests {
 		engine.reset();
 		engine.initialise();
 
-		let mut agent1 = Agent {
-			position: Position::new(10,10),
-			id: 0,
-			genome: Rc::new(RefCell::new(Genome::blank(0))),
-			current_sense: None
-		};
+		let agent1 = Agent::new(0, Position::new(10,10), Genome::blank(0));
 		engine.agents.insert(0, agent1);
-		engine.process_agents();
+		engine.process_agents().unwrap();
 
 		println!("{:?}", engine.agents);
 		assert_eq!(1,1);
 	}
-}
\ No newline at end of file
+
+	#[test]
+	fn rejects_invalid_input() {
+		let config = EngineConfig {
+			size: [2f64, 2f64],
+			agent_count: 20,
+			food_spread: 0f64,
+			..EngineConfig::default()
+		};
+		let Err(Error::InvalidConfig(problems)) = Engine::new(config) else {
+			panic!("invalid engine config accepted");
+		};
+		assert!(problems.contains("agent_count") && problems.contains("food_spread"), "{}", problems);
+
+		// sizes and spreads that would overflow the tile count or swamp the food sampler
+		for (size, food_spread) in [(f64::INFINITY, 2.5), (f64::NAN, 2.5), (1e12, 2.5), (100f64, 1e-9), (100f64, f64::NAN)] {
+			let config = EngineConfig { size: [size, size], food_spread, ..EngineConfig::default() };
+			assert!(matches!(config.validate(), Err(Error::InvalidConfig(_))), "{} {}", size, food_spread);
+		}
+
+		// visions, food rates and hidden layers that would exhaust memory or stall every round
+		for config in [
+			EngineConfig { vision_radius: 100000, ..EngineConfig::default() },
+			EngineConfig { food_rate: 1e12, ..EngineConfig::default() },
+			EngineConfig { food_rate: f64::INFINITY, ..EngineConfig::default() }
+		] {
+			assert!(matches!(config.validate(), Err(Error::InvalidConfig(_))), "{:?}", config);
+		}
+		for hidden_nodes in [usize::MAX, NetworkShape::MAX_WEIGHTS / 10] {
+			let config = OrchestratorConfig { hidden_nodes, ..OrchestratorConfig::default() };
+			assert!(matches!(config.validate(&EngineConfig::default()), Err(Error::InvalidConfig(_))), "{}", hidden_nodes);
+		}
+		assert!(OrchestratorConfig::default().validate(&EngineConfig::default()).is_ok());
+
+		let mut engine = get_engine();
+		let actions = HashMap::from([(7, Action::Move(Direction::Up))]);
+		assert!(matches!(engine.apply_actions(actions), Err(Error::UnknownAgent(7))));
+
+		let engine_config = EngineConfig {
+			size: [2f64, 1f64],
+			agent_count: 6,
+			..EngineConfig::default()
+		};
+		let mut orchestrator = Orchestrator::with_config(engine_config, OrchestratorConfig::default()).unwrap();
+		assert!(matches!(orchestrator.genepool.get_genome(99), Err(Error::UnknownGenome(99))));
+		for id in 0..7 {
+			orchestrator.genepool.add_genome(Genome::blank(id));
+		}
+		let ids = (0..7).collect::<Vec<u64>>();
+		assert!(matches!(orchestrator.build_agents(&ids), Err(Error::MapFull { agents: 7, tiles: 6 })));
+
+		// a genome evolved with another vision radius can't read this engine's senses
+		orchestrator.genepool.add_genome(Genome::new(8, NetworkShape { inputs: 9, hidden: 4 }));
+		assert!(matches!(orchestrator.build_agents(&[8]), Err(Error::InvalidConfig(_))));
+	}
+
+	#[test]
+	fn food_spawns_on_far_edges() {
+		random::reseed(5);
+		let mut engine = Engine::new(EngineConfig {
+			size: [1f64, 1f64],
+			agent_count: 0,
+			food_spread: 5f64,
+			food_rate: 1f64,
+			..EngineConfig::default()
+		}).unwrap();
+		engine.begin_match(HashMap::new(), 0);
+		for _ in 0..20 {
+			engine.step_with(HashMap::new()).unwrap();
+		}
+
+		// positions run from 0 to size inclusive, so all four tiles fill up
+		assert_eq!(engine.entities.len(), 4);
+		assert!(engine.entities.contains_key(&Position::new(1, 1)));
+	}
+}
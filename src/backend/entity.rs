@@ -1,19 +1,23 @@
-use cgmath::Vector2;
-use crate::backend::entity::EntityType::Food;
 use crate::backend::Position;
 
+/// Something other than an agent occupying a tile.
 #[derive(Debug)]
 pub struct Entity {
-	position: Position,
-	entity_type: EntityType
+	/// the tile it occupies
+	pub position: Position,
+	/// what it is
+	pub entity_type: EntityType
 }
 
+/// The kinds of Entity.
 #[derive(Debug)]
 pub enum EntityType {
+	/// eaten by the first agent to step onto its tile
 	Food
 }
 
 impl Entity {
+	/// An entity of `entity_type` on the tile `pos`.
 	pub fn new(pos: Position, entity_type: EntityType) -> Self {
 		Self {
 			position: pos,
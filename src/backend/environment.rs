@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+use crate::backend::agent::{Agent, AgentSense};
+use crate::backend::engine::{Engine, EngineConfig};
+use crate::backend::error::{Error, Result};
+use crate::backend::map::Action;
+
+/// What every agent sees and earned after one Environment::step.
+#[derive(Debug, Clone)]
+pub struct Transition {
+	/// what each agent sees after the step
+	pub observations: HashMap<u64, AgentSense>,
+	/// food eaten by each agent during the step
+	pub rewards: HashMap<u64, f64>,
+	/// set by the match's last round, after which `step` fails until the next `reset`
+	pub done: bool
+}
+
+/// Gym-style control of an Engine for scripted policies, RL agents and tests: `reset` begins a
+/// match and `step` plays one round with actions chosen by the caller rather than by genomes.
+#[derive(Debug)]
+pub struct Environment {
+	/// the engine the matches are played on, for inspecting the map and agents
+	pub engine: Engine
+}
+
+impl Environment {
+	/// An environment playing in a world of `config`; call `reset` to begin a match.
+	pub fn new(config: EngineConfig) -> Result<Self> {
+		Ok(Self {
+			engine: Engine::new(config)?
+		})
+	}
+
+	/// Begins a match with an agent for every id on distinct random tiles and returns what each sees.
+	/// The agents are idle, moving only as `step` tells them.
+	pub fn reset(&mut self, agent_ids: &[u64]) -> Result<HashMap<u64, AgentSense>> {
+		let positions = self.engine.config.spawn_positions(agent_ids.len())?;
+		let agents = agent_ids.iter()
+			.zip(positions)
+			.map(|(id, position)| (*id, Agent::idle(*id, position)))
+			.collect::<HashMap<u64, Agent>>();
+		Ok(self.reset_with(agents))
+	}
+
+	/// Like `reset`, with agents placed by the caller.
+	pub fn reset_with(&mut self, agents: HashMap<u64, Agent>) -> HashMap<u64, AgentSense> {
+		self.engine.begin_match(agents, 0);
+		self.engine.observe()
+	}
+
+	/// Plays one round in which every agent in `actions` takes its action and the rest stay put.
+	/// Fails without playing the round if an action is for an agent not in the match, or if the
+	/// match is already over.
+	pub fn step(&mut self, actions: HashMap<u64, Action>) -> Result<Transition> {
+		if self.engine.game_concluded {
+			return Err(Error::MatchOver);
+		}
+		let food_eaten = self.engine.step_with(actions)?;
+		let mut rewards = self.engine.agents.keys()
+			.map(|id| (*id, 0f64))
+			.collect::<HashMap<u64, f64>>();
+		for (id, _) in food_eaten {
+			*rewards.get_mut(&id).unwrap() += 1f64;
+		}
+
+		Ok(Transition {
+			observations: self.engine.observe(),
+			rewards,
+			done: self.engine.game_concluded
+		})
+	}
+}
@@ -0,0 +1,60 @@
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod environment_tests {
+	use std::collections::HashMap;
+	use crate::backend::engine::EngineConfig;
+	use crate::backend::environment::Environment;
+	use crate::backend::error::Error;
+	use crate::backend::map::{Action, Direction};
+	use crate::backend::random;
+
+	#[test]
+	fn external_actions_drive_the_match() {
+		random::seed(3);
+		let mut environment = Environment::new(EngineConfig {
+			size: [10f64, 10f64],
+			round_max: 5,
+			agent_count: 3,
+			food_spread: 1.5,
+			vision_radius: 2,
+			..EngineConfig::default()
+		}).unwrap();
+
+		let observations = environment.reset(&[4, 7, 9]).unwrap();
+		assert_eq!(observations.len(), 3);
+		assert!(observations.values().all(|sense| sense.map_tiles.len() == 25));
+
+		let unknown = HashMap::from([(5, Action::Move(Direction::Up))]);
+		assert!(matches!(environment.step(unknown), Err(Error::UnknownAgent(5))));
+		assert_eq!(environment.engine.round_idx, 0);
+
+		let mut rewards = HashMap::new();
+		let mut steps = 0;
+		loop {
+			// agent 9 is left out and stays put
+			let actions = HashMap::from([(4, Action::Move(Direction::Right)), (7, Action::Move(Direction::Down))]);
+			let transition = environment.step(actions).unwrap();
+			steps += 1;
+			for (id, reward) in transition.rewards {
+				*rewards.entry(id).or_insert(0f64) += reward;
+			}
+			if transition.done {
+				break;
+			}
+		}
+
+		let result = environment.engine.match_result();
+		assert_eq!(steps, 5);
+		assert_eq!(result.duration, 5);
+		assert_eq!(result.agent_stats[&4].steps_taken, 5);
+		assert_eq!(result.agent_stats[&9].steps_taken, 0);
+		for (id, stats) in &result.agent_stats {
+			assert_eq!(rewards[id], stats.food_eaten as f64);
+		}
+
+		let actions = HashMap::from([(4, Action::Move(Direction::Right))]);
+		assert!(matches!(environment.step(actions), Err(Error::MatchOver)));
+		environment.reset(&[4, 7, 9]).unwrap();
+		assert!(environment.step(HashMap::new()).is_ok());
+	}
+}
\ No newline at end of file
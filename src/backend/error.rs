@@ -0,0 +1,125 @@
+use std::fmt;
+use std::io;
+
+/// Everything that can go wrong in the simulator, from invalid configs to failed writes.
+#[derive(Debug)]
+pub enum Error {
+	/// one or more config values the simulation can't run with, each naming its field
+	InvalidConfig(String),
+	/// a genome id that is neither in the pool nor in its archive
+	UnknownGenome(u64),
+	/// a saved genome whose network shape or weight count doesn't fit where it is loaded
+	InvalidGenome(String),
+	/// an action or recorded step for an agent that isn't in the match
+	UnknownAgent(u64),
+	/// an agent asked to act before it sensed its surroundings
+	NotSensed(u64),
+	/// more agents than the map has tiles to place them on
+	MapFull { agents: usize, tiles: usize },
+	/// a run asked to finish or benchmark without any genomes
+	EmptyPopulation,
+	/// a step asked of an environment whose match has ended
+	MatchOver,
+	/// a replay asked of an engine that wasn't recording one
+	NotRecorded,
+	/// reading or writing a file
+	Io(io::Error),
+	/// parsing or writing a JSON record, checkpoint or replay
+	Json(serde_json::Error),
+	/// parsing or writing a TOML config
+	Toml(String),
+	/// encoding a rendered frame or GIF
+	Image(image::ImageError)
+}
+
+/// Result of every fallible operation in the crate.
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Error::InvalidConfig(problems) => write!(f, "invalid config: {}", problems),
+			Error::UnknownGenome(id) => write!(f, "no genome {} in the pool", id),
+			Error::InvalidGenome(problem) => write!(f, "invalid genome: {}", problem),
+			Error::UnknownAgent(id) => write!(f, "no agent {} in the match", id),
+			Error::NotSensed(id) => write!(f, "agent {} has not sensed its surroundings yet", id),
+			Error::MapFull { agents, tiles } => write!(f, "can't place {} agents on {} tiles", agents, tiles),
+			Error::EmptyPopulation => f.write_str("the population is empty"),
+			Error::MatchOver => f.write_str("the match is over, reset to begin another"),
+			Error::NotRecorded => f.write_str("the match was not recorded"),
+			Error::Io(error) => error.fmt(f),
+			Error::Json(error) => error.fmt(f),
+			Error::Toml(error) => f.write_str(error),
+			Error::Image(error) => error.fmt(f)
+		}
+	}
+}
+
+impl std::error::Error for Error {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			Error::Io(error) => Some(error),
+			Error::Json(error) => Some(error),
+			Error::Image(error) => Some(error),
+			_ => None
+		}
+	}
+}
+
+impl From<io::Error> for Error {
+	fn from(error: io::Error) -> Self {
+		Error::Io(error)
+	}
+}
+
+impl From<serde_json::Error> for Error {
+	fn from(error: serde_json::Error) -> Self {
+		Error::Json(error)
+	}
+}
+
+impl From<toml::de::Error> for Error {
+	fn from(error: toml::de::Error) -> Self {
+		Error::Toml(error.to_string())
+	}
+}
+
+impl From<toml::ser::Error> for Error {
+	fn from(error: toml::ser::Error) -> Self {
+		Error::Toml(error.to_string())
+	}
+}
+
+impl From<image::ImageError> for Error {
+	fn from(error: image::ImageError) -> Self {
+		Error::Image(error)
+	}
+}
+
+/// Gathers every problem with a config before reporting them together.
+#[derive(Debug, Default)]
+pub(crate) struct Problems(Vec<String>);
+
+impl Problems {
+	pub fn check(&mut self, valid: bool, problem: &str) {
+		if !valid {
+			self.0.push(problem.to_string());
+		}
+	}
+
+	pub fn merge(&mut self, result: Result<()>) {
+		match result {
+			Ok(()) => {}
+			Err(Error::InvalidConfig(problems)) => self.0.push(problems),
+			Err(error) => self.0.push(error.to_string())
+		}
+	}
+
+	pub fn into_result(self) -> Result<()> {
+		if self.0.is_empty() {
+			Ok(())
+		} else {
+			Err(Error::InvalidConfig(self.0.join("; ")))
+		}
+	}
+}
@@ -0,0 +1,111 @@
+use serde::{Deserialize, Serialize};
+use crate::backend::agent::AgentStats;
+use crate::backend::behaviour::Behaviour;
+
+/// How a genome's food counts from several trials are reduced to one value.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Aggregation {
+	/// mean over trials
+	Mean,
+	/// median over trials, ignoring outlying trials
+	Median,
+	/// worst trial, favouring genomes that do well in every trial
+	Min
+}
+
+impl Aggregation {
+	/// Reduces `values` to one; 0 when there are none.
+	pub fn aggregate(&self, values: &[f64]) -> f64 {
+		if values.is_empty() {
+			return 0f64;
+		}
+
+		match self {
+			Aggregation::Mean => mean(values),
+			Aggregation::Median => {
+				let mut sorted = values.to_vec();
+				sorted.sort_by(|a, b| a.total_cmp(b));
+				let middle = sorted.len() / 2;
+				if sorted.len().is_multiple_of(2) {
+					(sorted[middle - 1] + sorted[middle]) / 2f64
+				} else {
+					sorted[middle]
+				}
+			}
+			Aggregation::Min => values.iter().cloned().fold(f64::INFINITY, f64::min)
+		}
+	}
+}
+
+/// What selection optimises for.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum FitnessMode {
+	/// select on food eaten alone
+	Food,
+	/// select on behavioural novelty alone, see NoveltyArchive
+	Novelty,
+	/// weighted blend of food and novelty, each normalised by the population's best
+	Combined { novelty_weight: f64 }
+}
+
+/// The stats a genome collected over every trial of one generation, reduced to a single fitness.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Evaluation {
+	/// the genome's stats from each trial
+	pub trials: Vec<AgentStats>,
+	/// the genome's behaviour in each trial, in the same order
+	pub behaviours: Vec<Behaviour>,
+	/// the score optimizers select on; equal to `food` unless a FitnessMode says otherwise
+	pub fitness: f64,
+	/// food eaten in each trial, reduced by the run's Aggregation
+	pub food: f64,
+	/// variance of food eaten across trials
+	pub variance: f64,
+	/// see NoveltyArchive::score; left at 0 when selecting on food alone
+	pub novelty: f64
+}
+
+impl Evaluation {
+	/// Evaluates a genome on food alone; the Orchestrator scores novelty afterwards.
+	pub fn new(trials: Vec<AgentStats>, behaviours: Vec<Behaviour>, aggregation: Aggregation) -> Self {
+		let food_eaten = trials.iter()
+			.map(|stats| stats.food_eaten as f64)
+			.collect::<Vec<f64>>();
+		let trial_mean = mean(&food_eaten);
+		let variance = if food_eaten.is_empty() {
+			0f64
+		} else {
+			food_eaten.iter().map(|food| (food - trial_mean).powi(2)).sum::<f64>() / food_eaten.len() as f64
+		};
+
+		let food = aggregation.aggregate(&food_eaten);
+
+		Self {
+			fitness: food,
+			food,
+			variance,
+			novelty: 0f64,
+			trials,
+			behaviours
+		}
+	}
+
+	/// The genome's behaviour descriptor averaged over its trials.
+	pub fn descriptor(&self) -> Vec<f64> {
+		Behaviour::mean_descriptor(&self.behaviours)
+	}
+
+	/// Food eaten summed over every trial.
+	pub fn total_food_eaten(&self) -> usize {
+		self.trials.iter().map(|stats| stats.food_eaten).sum()
+	}
+}
+
+fn mean(values: &[f64]) -> f64 {
+	if values.is_empty() {
+		return 0f64;
+	}
+	values.iter().sum::<f64>() / values.len() as f64
+}
@@ -0,0 +1,43 @@
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod evaluation_tests {
+	use crate::backend::agent::AgentStats;
+	use crate::backend::evaluation::{Aggregation, Evaluation};
+
+	fn evaluate(food: &[usize], aggregation: Aggregation) -> Evaluation {
+		let trials = food.iter()
+			.map(|food_eaten| AgentStats { food_eaten: *food_eaten, steps_taken: 10 })
+			.collect();
+		Evaluation::new(trials, Vec::new(), aggregation)
+	}
+
+	#[test]
+	fn mean_of_trials() {
+		let evaluation = evaluate(&[2, 4, 9], Aggregation::Mean);
+		assert_eq!(evaluation.food, 5.0);
+		assert_eq!(evaluation.fitness, 5.0);
+		assert!((evaluation.variance - 26.0 / 3.0).abs() < 1e-9);
+	}
+
+	#[test]
+	fn median_of_odd_trials() {
+		let evaluation = evaluate(&[9, 1, 4], Aggregation::Median);
+		assert_eq!(evaluation.food, 4.0);
+		assert_eq!(evaluation.fitness, 4.0);
+	}
+
+	#[test]
+	fn median_of_even_trials() {
+		let evaluation = evaluate(&[7, 1, 3, 12], Aggregation::Median);
+		assert_eq!(evaluation.food, 5.0);
+	}
+
+	#[test]
+	fn min_of_trials() {
+		let evaluation = evaluate(&[6, 3, 8, 3], Aggregation::Min);
+		assert_eq!(evaluation.food, 3.0);
+		assert_eq!(evaluation.fitness, 3.0);
+		// variance describes the trials whatever the aggregation
+		assert_eq!(evaluation.variance, evaluate(&[6, 3, 8, 3], Aggregation::Mean).variance);
+	}
+}
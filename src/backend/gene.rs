@@ -1,146 +1,398 @@
-use std::cell::RefCell;
-use std::collections::HashMap;
-use std::env::var;
-use std::hash::{Hash, Hasher};
-use std::rc::Rc;
-use rand::prelude::SliceRandom;
-use rand::{RngCore, thread_rng};
-use tch::{CModule, Device, nn, Tensor, kind};
-use tch::nn::{Module, Sequential, VarStore};
-use crate::backend::agent::{Agent, AgentStats};
-use crate::backend::engine::Engine;
-use crate::backend::Position;
-// use tch::nn::CModule;
+use std::collections::{HashMap, HashSet};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use crate::backend::random::rng;
+use crate::backend::agent::{AgentSense, AgentStats};
+use crate::backend::archive::GenomeArchive;
+use crate::backend::error::{Error, Result};
+use crate::backend::evaluation::Evaluation;
+use crate::backend::lineage::Lineage;
+use crate::backend::map::Action;
+use crate::backend::mutation::MutationOperator;
+use crate::backend::network::{Network, NetworkShape, OUTPUT_NODES};
+use crate::backend::policy::{Policy, PolicyMode};
 
-#[derive(Debug)]
+/// Where a genome came from: the genomes it was derived from and how.
+#[derive(Debug, Clone, Default)]
+pub struct Ancestry {
+	/// ids of the genomes it was copied or crossed from; empty for fresh genomes
+	pub parents: Vec<u64>,
+	/// human-readable description of every mutation applied since, if any
+	pub mutation: Option<String>
+}
+
+/// The weights of one agent's network, plus the mutation step sizes and ancestry they evolve with.
+#[derive(Debug, Clone)]
 pub struct Genome {
-	var_store: VarStore,
-	module: Sequential,
+	network: Network,
+	/// per-weight mutation standard deviations, evolved alongside the weights by MutationOperator::SelfAdaptive
+	pub step_sizes: Vec<f32>,
+	/// unique within a run
 	pub id: u64,
-	pub stats: AgentStats
+	/// where the genome came from
+	pub ancestry: Ancestry
 }
 
 impl Genome {
 
-	const MUTATION_STRENGTH: f64 = 0.5f64;
+	const INITIAL_STEP_SIZE: f32 = 0.1f32;
 
+	/// A randomly initialised genome with the default network shape.
 	pub fn blank(id: u64) -> Self {
-		let (net, var_store) = Network::get_network(None);
+		Genome::new(id, NetworkShape::DEFAULT)
+	}
+
+	/// A randomly initialised genome whose network has the given shape.
+	pub fn new(id: u64, shape: NetworkShape) -> Self {
 		Genome {
-			var_store,
-			module: net,
+			network: Network::new(shape),
+			step_sizes: vec![Genome::INITIAL_STEP_SIZE; shape.weight_count()],
 			id,
-			stats: AgentStats::new()
+			ancestry: Ancestry::default()
 		}
 	}
 
-	pub fn test(&mut self) {
-		let data = Tensor::rand(&[1,49], kind::FLOAT_CPU).set_requires_grad(false);
-		let out1 = self.module.forward(&data);
-		out1.print();
+	/// The shape of the genome's network.
+	pub fn shape(&self) -> NetworkShape {
+		self.network.shape()
+	}
 
-		self.mutate();
+	/// Applies `operators` in order, appending them to the ancestry's mutation description.
+	pub fn mutate(&mut self, operators: &[MutationOperator]) {
+		let mut rng = rng();
+		let mut weights = self.network.weights();
+		for operator in operators {
+			operator.apply(&mut weights, &mut self.step_sizes, &mut rng);
+		}
+		self.network.set_weights(&weights);
 
-		let out2 = self.module.forward(&data);
-		out2.print();
+		let description = operators.iter()
+			.map(|operator| format!("{:?}", operator))
+			.collect::<Vec<String>>()
+			.join(", ");
+		self.ancestry.mutation = Some(match self.ancestry.mutation.take() {
+			None => description,
+			Some(previous) => format!("{}; {}", previous, description)
+		});
+	}
 
-		let dist = out2.multinomial(1, false).int64_value(&[0,0]);
-		println!("{}", dist);
-		// dist.print();
+	/// A child of this genome with identical weights and a fresh id.
+	pub fn copy(&self) -> Genome {
+		Genome {
+			id: rng().next_u64(),
+			network: self.network.clone(),
+			step_sizes: self.step_sizes.clone(),
+			ancestry: Ancestry {
+				parents: vec![self.id],
+				mutation: None
+			}
+		}
 	}
 
-	pub fn mutate(&mut self) {
+	/// flattened network weights, laid out identically by every network backend
+	pub fn weights(&self) -> Vec<f32> {
+		self.network.weights()
+	}
 
-		for (_, mut var) in self.var_store.variables() {
-			var.set_requires_grad(false);
-			let new_var = var.shallow_clone() + (Tensor::rand(var.size().as_slice(), kind::FLOAT_CPU) * Genome::MUTATION_STRENGTH);
-			var.copy_(&new_var);
-		}
-		let (module, var_store) = Network::get_network(Some(&self.var_store));
+	/// Replaces the network's weights, laid out as `weights` returns them.
+	pub fn set_weights(&mut self, weights: &[f32]) {
+		self.network.set_weights(weights);
+	}
 
-		self.module = module;
-		self.var_store = var_store;
+	/// Mean of the per-weight mutation step sizes.
+	pub fn mean_step_size(&self) -> f32 {
+		self.step_sizes.iter().sum::<f32>() / self.step_sizes.len() as f32
 	}
 
-	pub fn copy(&self) -> Genome {
-		let (module, var_store) = Network::get_network(Some(&self.var_store));
-		return Genome {
-			id: thread_rng().next_u64(),
-			module,
-			var_store,
-			stats: self.stats.clone()
-		}
+	/// The network's action probabilities for one input, indexed by Action::index.
+	pub fn action_probabilities(&self, data: &[f32]) -> Vec<f32> {
+		Network::forward_batch(&[&self.network], data)
+	}
+
+	/// Picks an action index for one input according to `mode`.
+	pub fn forward(&self, data: &[f32], mode: PolicyMode) -> i64 {
+		Genome::forward_batch(&[self], data, mode)[0]
+	}
+
+	/// Evaluates every genome against its own row of `data` ([N, inputs], row-major) in a
+	/// single call and picks one action index per genome according to `mode`.
+	pub fn forward_batch(genomes: &[&Genome], data: &[f32], mode: PolicyMode) -> Vec<i64> {
+		let networks = genomes.iter().map(|genome| &genome.network).collect::<Vec<&Network>>();
+		let probabilities = Network::forward_batch(&networks, data);
+
+		let mut rng = rng();
+		probabilities.chunks(OUTPUT_NODES)
+			.map(|row| mode.select(row, &mut rng))
+			.collect()
 	}
+}
 
-	pub fn forward(&self, data: &Tensor) -> i64 {
-		return self.module.forward(data).multinomial(1, false).int64_value(&[0,0]);
+impl Policy for Genome {
+	fn act(&self, sense: &AgentSense, mode: PolicyMode) -> Action {
+		Action::from_index(self.forward(&sense.input(), mode))
 	}
 }
 
-pub struct Network {
-	genome: Genome,
-	module: Sequential
+/// What a GenomePool keeps in memory when it evicts genomes, see GenomePool::retain.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RetentionPolicy {
+	/// best genomes by recorded fitness kept in memory however old they are
+	pub elites: usize,
+	/// generations of ancestors kept in memory behind every live genome
+	pub ancestor_depth: usize,
+	/// most recent evaluations kept per genome; older ones live on in the Lineage
+	pub history: usize
 }
 
-impl Network {
-
-	const HIDDEN_NODES: i64 = 32;
-
-	pub fn get_network(vs: Option<&nn::VarStore>) -> (Sequential, VarStore) {
-		let mut new_var_store = VarStore::new(Device::Cpu);
-		new_var_store.freeze();
-
-		let path = &new_var_store.root();
-		let default_module = nn::seq()
-			.add(nn::linear(
-				path / "layer1",
-				Engine::DISTANCE_VISIBLE_BLOCKS as i64,
-				Network::HIDDEN_NODES,
-				Default::default(),
-			))
-			.add_fn(|xs| xs.tanh())
-			.add(nn::linear(path / "final", Network::HIDDEN_NODES, 4, Default::default()))
-			.add_fn(|xs| xs.softmax(1, kind::Kind::Float));
-
-		match vs {
-			None => {}
-			Some(new_weights) => {
-				new_var_store.copy(new_weights);
-				new_var_store.freeze();
-			}
+impl Default for RetentionPolicy {
+	fn default() -> Self {
+		Self {
+			elites: 8,
+			ancestor_depth: 1,
+			history: 16
 		}
+	}
+}
 
-		(default_module, new_var_store)
+/// One genome's results for one generation. Recorded once and never modified, so a genome's
+/// history only ever reflects matches it actually played.
+#[derive(Debug, Clone)]
+pub struct MatchStats {
+	/// the generation the results are from
+	pub generation: usize,
+	/// see Evaluation::trials
+	pub trials: Vec<AgentStats>,
+	/// see Evaluation::fitness
+	pub fitness: f64,
+	/// see Evaluation::food
+	pub food: f64,
+	/// see Evaluation::variance
+	pub variance: f64
+}
+
+impl MatchStats {
+	/// Records `evaluation` as the genome's results for `generation`.
+	pub fn from_evaluation(generation: usize, evaluation: &Evaluation) -> Self {
+		Self {
+			generation,
+			trials: evaluation.trials.clone(),
+			fitness: evaluation.fitness,
+			food: evaluation.food,
+			variance: evaluation.variance
+		}
 	}
+
+	/// Food eaten summed over every trial.
+	pub fn food_eaten(&self) -> usize {
+		self.trials.iter().map(|stats| stats.food_eaten).sum()
+	}
+
+	/// Steps taken summed over every trial.
+	pub fn steps_taken(&self) -> usize {
+		self.trials.iter().map(|stats| stats.steps_taken).sum()
+	}
+}
+
+/// What the pool remembers about a genome it holds; see Lineage for the whole run's ancestry.
+#[derive(Debug, Clone)]
+pub struct GenomeSummary {
+	/// see Genome::id
+	pub id: u64,
+	/// the generation the genome was added to the pool in
+	pub generation: usize,
+	/// best fitness recorded for the genome so far
+	pub fitness: Option<f64>
 }
 
+/// Every genome of a run by id, with the results each has recorded. Genomes that fall out of
+/// use can be evicted to a GenomeArchive and are loaded back on demand.
 #[derive(Debug)]
 pub struct GenomePool {
-	pool: HashMap<u64, Rc<RefCell<Genome>>>,
+	pool: HashMap<u64, Genome>,
+	summaries: HashMap<u64, GenomeSummary>,
+	history: HashMap<u64, Vec<MatchStats>>,
+	// where evicted genomes are written; without one they are dropped
+	archive: Option<GenomeArchive>,
+	generation: usize
+}
+
+impl Default for GenomePool {
+	fn default() -> Self {
+		GenomePool::new()
+	}
 }
 
 impl GenomePool {
+	/// An empty pool that drops evicted genomes.
 	pub fn new() -> Self {
 		Self {
 			pool: HashMap::new(),
+			summaries: HashMap::new(),
+			history: HashMap::new(),
+			archive: None,
+			generation: 0
 		}
 	}
 
-	pub fn add_genome(&mut self, id: u64, genome: Rc<RefCell<Genome>>) {
-		if !self.pool.contains_key(&id) {
-			self.pool.insert(id, genome);
+	/// A pool that writes evicted genomes to `archive` instead of dropping them.
+	pub fn with_archive(archive: GenomeArchive) -> Self {
+		Self {
+			archive: Some(archive),
+			..GenomePool::new()
 		}
 	}
 
-	pub fn get_genome(&self, id: u64) -> &Rc<RefCell<Genome>> {
-		self.pool.get(&id).unwrap()
+	/// Genomes added from now on are recorded as born in `generation`.
+	pub fn set_generation(&mut self, generation: usize) {
+		self.generation = generation;
 	}
 
-	// pub fn update_stats(&mut self, id: u64, new_stats: AgentStats) {
-	// 	let mut agent_stats = *self.stats.get(&id).unwrap();
-	//
-	// 	agent_stats.cumulative_food_eaten += new_stats.cumulative_food_eaten;
-	// 	agent_stats.steps_taken += new_stats.steps_taken;
-	// 	agent_stats.food_eaten += new_stats.food_eaten;
-	// }
-}
\ No newline at end of file
+	/// Adds a genome to the pool; one already held under the same id is kept instead.
+	pub fn add_genome(&mut self, genome: Genome) {
+		let generation = self.generation;
+		self.summaries.entry(genome.id).or_insert_with(|| GenomeSummary {
+			id: genome.id,
+			generation,
+			fitness: None
+		});
+		self.pool.entry(genome.id).or_insert(genome);
+	}
+
+	/// A genome held in memory; see `load` for evicted genomes.
+	pub fn get_genome(&self, id: u64) -> Result<&Genome> {
+		self.pool.get(&id).ok_or(Error::UnknownGenome(id))
+	}
+
+	/// Whether the genome is held in memory; see `load` for evicted genomes.
+	pub fn contains(&self, id: u64) -> bool {
+		self.pool.contains_key(&id)
+	}
+
+	/// A genome held in memory, to change in place.
+	pub fn get_genome_mut(&mut self, id: u64) -> Result<&mut Genome> {
+		self.pool.get_mut(&id).ok_or(Error::UnknownGenome(id))
+	}
+
+	/// Number of genomes held in memory.
+	pub fn len(&self) -> usize {
+		self.pool.len()
+	}
+
+	/// Every genome held in memory.
+	pub fn genomes(&self) -> impl Iterator<Item = &Genome> {
+		self.pool.values()
+	}
+
+	/// Whether no genomes are held in memory.
+	pub fn is_empty(&self) -> bool {
+		self.pool.is_empty()
+	}
+
+	/// A copy of the genome, read back from the archive if it has been evicted.
+	pub fn load(&self, id: u64) -> Result<Genome> {
+		if let Some(genome) = self.pool.get(&id) {
+			return Ok(genome.clone());
+		}
+		let archived = match &self.archive {
+			Some(archive) => archive.load(id)?,
+			None => None
+		};
+		archived.ok_or(Error::UnknownGenome(id))
+	}
+
+	/// Appends the genome's results for `generation` to its history.
+	pub fn record_evaluation(&mut self, id: u64, generation: usize, evaluation: &Evaluation) {
+		let fitness = evaluation.fitness;
+		if let Some(summary) = self.summaries.get_mut(&id) {
+			summary.fitness = Some(summary.fitness.map_or(fitness, |best| best.max(fitness)));
+		}
+		self.history.entry(id)
+			.or_default()
+			.push(MatchStats::from_evaluation(generation, evaluation));
+	}
+
+	/// The genome's retained evaluations, oldest first; see RetentionPolicy::history.
+	pub fn history(&self, id: u64) -> &[MatchStats] {
+		self.history.get(&id).map_or(&[], |history| history.as_slice())
+	}
+
+	/// The genome's most recent evaluation.
+	pub fn latest(&self, id: u64) -> Option<&MatchStats> {
+		self.history(id).last()
+	}
+
+	/// Food eaten by the genome itself across its retained evaluations.
+	pub fn cumulative_food_eaten(&self, id: u64) -> usize {
+		self.history(id).iter().map(|stats| stats.food_eaten()).sum()
+	}
+
+	/// Every genome evaluated in `generation`, with its results from that generation.
+	pub fn generation_history(&self, generation: usize) -> Vec<(u64, &MatchStats)> {
+		let mut records = self.history.iter()
+			.flat_map(|(id, history)| history.iter()
+				.filter(|stats| stats.generation == generation)
+				.map(|stats| (*id, stats)))
+			.collect::<Vec<(u64, &MatchStats)>>();
+		records.sort_by_key(|(id, _)| *id);
+		records
+	}
+
+	/// What the pool remembers about a genome it holds.
+	pub fn summary(&self, id: u64) -> Option<&GenomeSummary> {
+		self.summaries.get(&id)
+	}
+
+	/// Ids of every genome held in memory that was born in `generation`.
+	pub fn by_generation(&self, generation: usize) -> Vec<u64> {
+		let mut ids = self.summaries.values()
+			.filter(|summary| summary.generation == generation)
+			.map(|summary| summary.id)
+			.collect::<Vec<u64>>();
+		ids.sort();
+		ids
+	}
+
+	/// The `count` fittest genomes held in memory, best first. Elites are never evicted, so these
+	/// are the fittest of the run as long as `count` is within RetentionPolicy::elites.
+	pub fn top_by_fitness(&self, count: usize) -> Vec<u64> {
+		let mut scored = self.summaries.values()
+			.filter_map(|summary| summary.fitness.map(|fitness| (summary.id, fitness)))
+			.collect::<Vec<(u64, f64)>>();
+		scored.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+		scored.into_iter()
+			.take(count)
+			.map(|(id, _)| id)
+			.collect()
+	}
+
+	/// Keeps `live`, their recent ancestors in `lineage` and the all-time elites in memory and evicts
+	/// every other genome to the archive, along with its summary and history. Returns how many genomes
+	/// were evicted.
+	pub fn retain(&mut self, live: &[u64], policy: RetentionPolicy, lineage: &Lineage) -> Result<usize> {
+		let mut keep = live.iter().cloned().collect::<HashSet<u64>>();
+		for id in live {
+			keep.extend(lineage.ancestors_within(*id, policy.ancestor_depth));
+		}
+		keep.extend(self.top_by_fitness(policy.elites));
+
+		let evicted = self.pool.keys()
+			.filter(|id| !keep.contains(id))
+			.cloned()
+			.collect::<Vec<u64>>();
+		for id in &evicted {
+			// only let go of a genome once it is safely on disk
+			if let Some(archive) = &mut self.archive {
+				archive.store(&self.pool[id])?;
+			}
+			self.pool.remove(id);
+			self.summaries.remove(id);
+			self.history.remove(id);
+		}
+		for history in self.history.values_mut() {
+			let excess = history.len().saturating_sub(policy.history);
+			history.drain(..excess);
+		}
+		Ok(evicted.len())
+	}
+}
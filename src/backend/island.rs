@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use crate::backend::{Orchestrator, OrchestratorConfig};
+use crate::backend::engine::EngineConfig;
+use crate::backend::error::{Problems, Result};
+use crate::backend::evaluation::Evaluation;
+use crate::backend::gene::Genome;
+
+/// Which islands receive each island's migrants.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Topology {
+	/// island i sends its migrants to island i + 1
+	Ring,
+	/// every island sends its migrants to every other island
+	FullyConnected
+}
+
+impl Topology {
+	/// The islands `island` sends its migrants to, out of `island_count`.
+	pub fn destinations(&self, island: usize, island_count: usize) -> Vec<usize> {
+		if island_count < 2 {
+			return Vec::new();
+		}
+
+		match self {
+			Topology::Ring => vec![(island + 1) % island_count],
+			Topology::FullyConnected => (0..island_count).filter(|other| *other != island).collect()
+		}
+	}
+}
+
+/// How an Archipelago splits the population; the `[islands]` table of an ExperimentConfig.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IslandConfig {
+	/// number of islands; each plays a population of engine.agent_count
+	pub islands: usize,
+	/// which islands receive each island's migrants
+	pub topology: Topology,
+	/// generations between migrations
+	pub migration_interval: usize,
+	/// how many of each island's best genomes are sent to every destination
+	pub migrants: usize
+}
+
+impl Default for IslandConfig {
+	fn default() -> Self {
+		Self {
+			islands: 4,
+			topology: Topology::Ring,
+			migration_interval: 10,
+			migrants: 2
+		}
+	}
+}
+
+impl IslandConfig {
+	/// Checks the settings against the engine every island plays in and the optimizer every island
+	/// evolves with, see ExperimentConfig::validate.
+	pub fn validate(&self, engine: &EngineConfig, evolution: &OrchestratorConfig) -> Result<()> {
+		let mut problems = Problems::default();
+		problems.check(evolution.optimizer.accepts_migrants(), "islands need an optimizer that takes in migrants, i.e. genetic or map_elites");
+		problems.check(self.islands > 0, "islands.islands must be at least 1");
+		problems.check(self.migration_interval > 0, "islands.migration_interval must be at least 1");
+		problems.check(self.migrants <= engine.agent_count, "islands.migrants must not exceed engine.agent_count");
+		problems.into_result()
+	}
+}
+
+/// Independent populations, each with its own Orchestrator (and so its own GenomePool, optimizer and
+/// engines), that periodically swap their best genomes.
+pub struct Archipelago {
+	/// one orchestrator per island, in island order
+	pub islands: Vec<Orchestrator>,
+	config: IslandConfig
+}
+
+impl Archipelago {
+	/// One orchestrator per island, each writing its artefacts to `island_<n>` in the output directory.
+	pub fn new(engine_config: EngineConfig, orchestrator_config: OrchestratorConfig, config: IslandConfig) -> Result<Self> {
+		config.validate(&engine_config, &orchestrator_config)?;
+		let islands = (0..config.islands.max(1))
+			.map(|island| {
+				// islands write their artefacts side by side rather than over each other
+				let mut config = orchestrator_config.clone();
+				config.output_dir = config.output_dir.map(|output_dir| output_dir.join(format!("island_{}", island)));
+				Orchestrator::with_config(engine_config.clone(), config)
+			})
+			.collect::<Result<Vec<Orchestrator>>>()?;
+
+		Ok(Self {
+			islands,
+			config
+		})
+	}
+
+	/// Evolves every island for `generations` generations, migrating every `migration_interval`.
+	/// Returns each island's final population, in island order.
+	pub fn run(&mut self, generations: usize) -> Result<Vec<Vec<u64>>> {
+		let mut populations = vec![Vec::new(); self.islands.len()];
+		for generation in 0..generations {
+			let evaluations = self.islands.iter_mut()
+				.map(|island| island.run_generation(generation))
+				.collect::<Result<Vec<HashMap<u64, Evaluation>>>>()?;
+			populations = evaluations.iter().map(Orchestrator::population).collect();
+
+			let interval = self.config.migration_interval.max(1);
+			if (generation + 1).is_multiple_of(interval) {
+				self.migrate(&evaluations, generation)?;
+			}
+		}
+		Ok(populations)
+	}
+
+	/// Finishes every island that ran, see Orchestrator::finish. Returns each island's best genome
+	/// and its benchmark, in island order.
+	pub fn finish(&self, populations: &[Vec<u64>]) -> Result<Vec<(u64, Evaluation)>> {
+		self.islands.iter()
+			.zip(populations)
+			.filter(|(_, population)| !population.is_empty())
+			.map(|(island, population)| island.finish(population))
+			.collect()
+	}
+
+	/// Sends each island's best genomes of the generation to its destinations under the topology.
+	pub fn migrate(&mut self, evaluations: &[HashMap<u64, Evaluation>], generation: usize) -> Result<()> {
+		let island_count = self.islands.len();
+		let mut arrivals: Vec<Vec<(Genome, Evaluation)>> = vec![Vec::new(); island_count];
+
+		for (island, island_evaluations) in evaluations.iter().enumerate() {
+			let mut ranked = island_evaluations.iter().collect::<Vec<(&u64, &Evaluation)>>();
+			ranked.sort_by(|(_, a), (_, b)| b.fitness.total_cmp(&a.fitness));
+
+			for destination in self.config.topology.destinations(island, island_count) {
+				for (id, evaluation) in ranked.iter().take(self.config.migrants) {
+					let genome = self.islands[island].genepool.get_genome(**id)?.clone();
+					arrivals[destination].push((genome, (*evaluation).clone()));
+				}
+			}
+		}
+
+		for (island, migrants) in self.islands.iter_mut().zip(arrivals) {
+			island.immigrate(migrants, generation)?;
+		}
+		Ok(())
+	}
+}
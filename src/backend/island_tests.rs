@@ -0,0 +1,94 @@
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod island_tests {
+	use std::collections::HashMap;
+	use crate::backend::OrchestratorConfig;
+	use crate::backend::config::ExperimentConfig;
+	use crate::backend::engine::EngineConfig;
+	use crate::backend::evaluation::{Aggregation, Evaluation};
+	use crate::backend::island::{Archipelago, IslandConfig, Topology};
+	use crate::backend::optimizer::OptimizerKind;
+
+	#[test]
+	fn topology_destinations() {
+		assert_eq!(Topology::Ring.destinations(0, 3), vec![1]);
+		assert_eq!(Topology::Ring.destinations(2, 3), vec![0]);
+		assert_eq!(Topology::FullyConnected.destinations(1, 3), vec![0, 2]);
+		assert!(Topology::Ring.destinations(0, 1).is_empty());
+	}
+
+	#[test]
+	fn migration_copies_best_genomes() {
+		let engine_config = EngineConfig {
+			food_spread: 2.5f64,
+			size: [20f64, 20f64],
+			round_max: 10,
+			agent_count: 6,
+			..EngineConfig::default()
+		};
+		let config = IslandConfig { islands: 2, topology: Topology::Ring, migration_interval: 1, migrants: 1 };
+		let mut archipelago = Archipelago::new(engine_config, OrchestratorConfig::default(), config).unwrap();
+
+		let evaluations = archipelago.islands.iter_mut()
+			.map(|island| {
+				let ids = island.optimizer.ask(&mut island.genepool).unwrap();
+				ids.iter()
+					.enumerate()
+					.map(|(rank, id)| {
+						let mut evaluation = Evaluation::new(Vec::new(), Vec::new(), Aggregation::Mean);
+						evaluation.fitness = rank as f64;
+						(*id, evaluation)
+					})
+					.collect::<HashMap<u64, Evaluation>>()
+			})
+			.collect::<Vec<HashMap<u64, Evaluation>>>();
+
+		archipelago.migrate(&evaluations, 0).unwrap();
+
+		for (island, destination) in [(0, 1), (1, 0)] {
+			let (best_id, _) = evaluations[island].iter()
+				.max_by(|(_, a), (_, b)| a.fitness.total_cmp(&b.fitness))
+				.unwrap();
+			assert!(archipelago.islands[destination].genepool.contains(*best_id));
+		}
+	}
+
+	#[test]
+	fn islands_from_experiment() {
+		let experiment: ExperimentConfig = toml::from_str("
+			generations = 2
+
+			[engine]
+			size = [20.0, 20.0]
+			round_max = 10
+			agent_count = 6
+
+			[evolution]
+			trials = 1
+
+			[islands]
+			islands = 3
+			topology = \"fully_connected\"
+			migration_interval = 1
+		").unwrap();
+		experiment.validate().unwrap();
+		let islands = experiment.islands.clone().unwrap();
+		assert_eq!(islands.topology, Topology::FullyConnected);
+		assert_eq!(islands.migrants, IslandConfig::default().migrants);
+
+		let mut archipelago = Archipelago::new(experiment.engine.clone(), experiment.orchestrator_config(), islands).unwrap();
+		let populations = archipelago.run(experiment.generations).unwrap();
+		assert_eq!(populations.len(), 3);
+		assert!(populations.iter().all(|population| !population.is_empty()));
+		assert_eq!(archipelago.finish(&populations).unwrap().len(), 3);
+
+		let too_many_migrants = IslandConfig { migrants: 7, ..IslandConfig::default() };
+		assert!(too_many_migrants.validate(&experiment.engine, &experiment.evolution).is_err());
+
+		// CMA-ES and NES would silently drop every migrant
+		let mut experiment = experiment;
+		experiment.evolution.optimizer = OptimizerKind::CmaEs { sigma: 0.1 };
+		assert!(experiment.validate().is_err());
+		assert!(Archipelago::new(experiment.engine.clone(), experiment.orchestrator_config(), IslandConfig::default()).is_err());
+	}
+}
@@ -0,0 +1,163 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use crate::backend::error::Result;
+use crate::backend::gene::Genome;
+
+/// How a genome did in one generation it was evaluated in.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvaluationRecord {
+	/// the generation evaluated in
+	pub generation: usize,
+	/// see Evaluation::fitness
+	pub fitness: f64,
+	/// see Evaluation::food
+	pub food: f64
+}
+
+/// One genome in a Lineage, with its parents and every evaluation it had.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LineageNode {
+	/// see Genome::id
+	pub id: u64,
+	/// see Ancestry::parents
+	pub parents: Vec<u64>,
+	/// the generation the genome was first evaluated in
+	pub birth_generation: usize,
+	/// see Ancestry::mutation
+	pub mutation: Option<String>,
+	/// oldest first
+	pub evaluations: Vec<EvaluationRecord>
+}
+
+impl LineageNode {
+	/// Highest fitness over every evaluation, if the genome has had any.
+	pub fn best_fitness(&self) -> Option<f64> {
+		self.evaluations.iter()
+			.map(|record| record.fitness)
+			.max_by(|a, b| a.total_cmp(b))
+	}
+}
+
+/// Ancestry graph of every genome a run has evaluated.
+#[derive(Debug, Clone, Default)]
+pub struct Lineage {
+	nodes: HashMap<u64, LineageNode>
+}
+
+impl Lineage {
+	/// An empty lineage.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Rebuilds a lineage from its nodes, as saved in a Checkpoint.
+	pub fn from_nodes(nodes: Vec<LineageNode>) -> Self {
+		Self {
+			nodes: nodes.into_iter().map(|node| (node.id, node)).collect()
+		}
+	}
+
+	/// Every genome recorded, in no particular order.
+	pub fn nodes(&self) -> impl Iterator<Item = &LineageNode> {
+		self.nodes.values()
+	}
+
+	/// Whether the genome `id` has been recorded.
+	pub fn contains(&self, id: u64) -> bool {
+		self.nodes.contains_key(&id)
+	}
+
+	/// The node recorded for the genome `id`.
+	pub fn get(&self, id: u64) -> Option<&LineageNode> {
+		self.nodes.get(&id)
+	}
+
+	/// Records `genome` as born in `generation`, unless it was recorded already.
+	pub fn record_birth(&mut self, genome: &Genome, generation: usize) {
+		self.nodes.entry(genome.id).or_insert_with(|| LineageNode {
+			id: genome.id,
+			parents: genome.ancestry.parents.clone(),
+			birth_generation: generation,
+			mutation: genome.ancestry.mutation.clone(),
+			evaluations: Vec::new()
+		});
+	}
+
+	/// Adds an evaluation to the genome `id`; unrecorded genomes are ignored.
+	pub fn record_evaluation(&mut self, id: u64, record: EvaluationRecord) {
+		if let Some(node) = self.nodes.get_mut(&id) {
+			node.evaluations.push(record);
+		}
+	}
+
+	/// Every known ancestor of `id`, nearest first: parents, then grandparents and so on.
+	pub fn ancestors(&self, id: u64) -> Vec<u64> {
+		self.ancestors_within(id, usize::MAX)
+	}
+
+	/// Known ancestors of `id` at most `depth` generations back, nearest first.
+	pub fn ancestors_within(&self, id: u64, depth: usize) -> Vec<u64> {
+		let mut ancestors = Vec::new();
+		let mut frontier = self.nodes.get(&id)
+			.map(|node| node.parents.iter().map(|parent| (*parent, 1)).collect::<VecDeque<(u64, usize)>>())
+			.unwrap_or_default();
+
+		while let Some((parent, distance)) = frontier.pop_front() {
+			if distance > depth || ancestors.contains(&parent) {
+				continue;
+			}
+			ancestors.push(parent);
+			if let Some(node) = self.nodes.get(&parent) {
+				frontier.extend(node.parents.iter().map(|grandparent| (*grandparent, distance + 1)));
+			}
+		}
+		ancestors
+	}
+
+	/// The lineage as a Graphviz digraph, edges running from parent to child.
+	pub fn to_dot(&self) -> String {
+		let mut dot = String::from("digraph lineage {\n\tnode [shape=box];\n");
+
+		let mut nodes = self.nodes.values().collect::<Vec<&LineageNode>>();
+		nodes.sort_by_key(|node| (node.birth_generation, node.id));
+		for node in &nodes {
+			let fitness = node.best_fitness()
+				.map(|fitness| format!("{:.2}", fitness))
+				.unwrap_or_else(|| "-".to_string());
+			let _ = writeln!(
+				dot,
+				"\t\"{}\" [label=\"{}\\ngen {}\\nfitness {}\", tooltip=\"{}\"];",
+				node.id,
+				node.id,
+				node.birth_generation,
+				fitness,
+				node.mutation.as_deref().unwrap_or("").replace('"', "'")
+			);
+		}
+		for node in &nodes {
+			for parent in &node.parents {
+				let _ = writeln!(dot, "\t\"{}\" -> \"{}\";", parent, node.id);
+			}
+		}
+
+		dot.push_str("}\n");
+		dot
+	}
+
+	/// Every node as a JSON array, ordered by birth generation and id.
+	pub fn to_json(&self) -> Result<String> {
+		let mut nodes = self.nodes.values().collect::<Vec<&LineageNode>>();
+		nodes.sort_by_key(|node| (node.birth_generation, node.id));
+		Ok(serde_json::to_string_pretty(&nodes)?)
+	}
+
+	/// Writes lineage.dot and lineage.json into `directory`.
+	pub fn export(&self, directory: &Path) -> Result<()> {
+		fs::write(directory.join("lineage.dot"), self.to_dot())?;
+		fs::write(directory.join("lineage.json"), self.to_json()?)?;
+		Ok(())
+	}
+}
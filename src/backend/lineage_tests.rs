@@ -0,0 +1,45 @@
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod lineage_tests {
+	use crate::backend::gene::Genome;
+	use crate::backend::lineage::{EvaluationRecord, Lineage};
+	use crate::backend::mutation::MutationOperator;
+
+	#[test]
+	fn tracks_ancestry() {
+		let root = Genome::blank(1);
+		let mut child = root.copy();
+		child.mutate(&[MutationOperator::SignFlip { probability: 0.5 }]);
+		let grandchild = child.copy();
+
+		let mut lineage = Lineage::new();
+		lineage.record_birth(&root, 0);
+		lineage.record_birth(&child, 1);
+		lineage.record_birth(&grandchild, 2);
+		lineage.record_evaluation(child.id, EvaluationRecord { generation: 1, fitness: 3.0, food: 3.0 });
+
+		assert_eq!(lineage.ancestors(grandchild.id), vec![child.id, root.id]);
+		let child_node = lineage.get(child.id).unwrap();
+		assert_eq!(child_node.parents, vec![root.id]);
+		assert!(child_node.mutation.as_ref().unwrap().contains("SignFlip"));
+		assert_eq!(child_node.best_fitness(), Some(3.0));
+
+		let dot = lineage.to_dot();
+		assert!(dot.contains(&format!("\"{}\" -> \"{}\";", root.id, child.id)));
+		let json: serde_json::Value = serde_json::from_str(&lineage.to_json().unwrap()).unwrap();
+		assert_eq!(json.as_array().unwrap().len(), 3);
+	}
+
+	#[test]
+	fn ancestors_nearest_first() {
+		let mut lineage = Lineage::new();
+		for (id, parents) in [(1, vec![]), (2, vec![]), (3, vec![1]), (4, vec![2]), (5, vec![3, 4])] {
+			let mut genome = Genome::blank(id);
+			genome.ancestry.parents = parents;
+			lineage.record_birth(&genome, 0);
+		}
+
+		assert_eq!(lineage.ancestors(5), vec![3, 4, 1, 2]);
+		assert_eq!(lineage.ancestors_within(5, 1), vec![3, 4]);
+	}
+}
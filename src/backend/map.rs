@@ -1,17 +1,45 @@
-struct Map {
-
-}
-
-#[derive(PartialEq, Clone, Copy)]
+/// What an agent does in one round.
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum Action {
+	/// step one tile that way, staying on the map; if another agent claimed that tile first, step another way
 	Move(Direction),
+	/// stay on the current tile
 	Reproduce
 }
 
-#[derive(PartialEq, Clone, Copy)]
+impl Action {
+	/// Number of distinct actions; a network's outputs only cover the four moves.
+	pub const COUNT: usize = 5;
+
+	/// The action's position in behaviour histograms and replays, the inverse of `from_index`.
+	pub fn index(&self) -> usize {
+		match self {
+			Action::Move(direction) => *direction as usize,
+			Action::Reproduce => 4
+		}
+	}
+
+	/// The action at `idx`, such as a network's chosen output; anything past the moves is Reproduce.
+	pub fn from_index(idx: i64) -> Self {
+		match idx {
+			0 => Action::Move(Direction::Up),
+			1 => Action::Move(Direction::Down),
+			2 => Action::Move(Direction::Left),
+			3 => Action::Move(Direction::Right),
+			_ => Action::Reproduce
+		}
+	}
+}
+
+/// A direction on the map, y growing downwards.
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum Direction {
+	/// towards y = 0
 	Up = 0,
+	/// away from y = 0
 	Down = 1,
+	/// towards x = 0
 	Left = 2,
+	/// away from x = 0
 	Right = 3
 }
\ No newline at end of file
@@ -0,0 +1,244 @@
+use std::fs::OpenOptions;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use crate::backend::error::Result;
+use crate::backend::evaluation::Evaluation;
+use crate::backend::gene::Genome;
+
+/// Summary of one generation, written to every MetricsSink the Orchestrator has.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct GenerationMetrics {
+	/// the generation these metrics describe, counting from 0
+	pub generation: usize,
+	/// genomes evaluated this generation
+	pub population: usize,
+	/// highest fitness in the population
+	pub best_fitness: f64,
+	/// mean fitness over the population
+	pub mean_fitness: f64,
+	/// median fitness over the population
+	pub median_fitness: f64,
+	/// standard deviation of fitness over the population
+	pub std_fitness: f64,
+	/// highest Evaluation::food in the population
+	pub best_food: f64,
+	/// mean RMS distance of each genome's weights from the population's mean weights
+	pub diversity: f64,
+	/// genomes grouped greedily by RMS weight distance below the species threshold
+	pub species: usize,
+	/// mean number of rounds the generation's matches lasted
+	pub match_duration: f64,
+	/// seconds spent on the whole generation
+	pub wall_time: f64
+}
+
+impl GenerationMetrics {
+	/// Column names, in the order CsvSink writes them.
+	pub const FIELDS: [&'static str; 11] = [
+		"generation", "population", "best_fitness", "mean_fitness", "median_fitness", "std_fitness",
+		"best_food", "diversity", "species", "match_duration", "wall_time"
+	];
+
+	/// Summarises a generation from its evaluations and the weights of the genomes evaluated.
+	pub fn new(
+		generation: usize,
+		evaluations: &[&Evaluation],
+		genomes: &[&Genome],
+		match_durations: &[usize],
+		species_threshold: f64,
+		wall_time: Duration
+	) -> Self {
+		let mut fitness = evaluations.iter()
+			.map(|evaluation| evaluation.fitness)
+			.collect::<Vec<f64>>();
+		fitness.sort_by(|a, b| a.total_cmp(b));
+
+		let mean_fitness = mean(&fitness);
+		let std_fitness = (fitness.iter()
+			.map(|value| (value - mean_fitness).powi(2))
+			.sum::<f64>() / fitness.len().max(1) as f64).sqrt();
+		let median_fitness = match fitness.len() {
+			0 => 0f64,
+			len if len.is_multiple_of(2) => (fitness[len / 2 - 1] + fitness[len / 2]) / 2f64,
+			len => fitness[len / 2]
+		};
+
+		let weights = genomes.iter()
+			.map(|genome| genome.weights())
+			.collect::<Vec<Vec<f32>>>();
+		let durations = match_durations.iter()
+			.map(|duration| *duration as f64)
+			.collect::<Vec<f64>>();
+
+		Self {
+			generation,
+			population: evaluations.len(),
+			best_fitness: fitness.last().cloned().unwrap_or(0f64),
+			mean_fitness,
+			median_fitness,
+			std_fitness,
+			best_food: evaluations.iter().map(|evaluation| evaluation.food).fold(0f64, f64::max),
+			diversity: diversity(&weights),
+			species: species_count(&weights, species_threshold),
+			match_duration: mean(&durations),
+			wall_time: wall_time.as_secs_f64()
+		}
+	}
+
+	pub(crate) fn values(&self) -> [String; 11] {
+		[
+			self.generation.to_string(),
+			self.population.to_string(),
+			self.best_fitness.to_string(),
+			self.mean_fitness.to_string(),
+			self.median_fitness.to_string(),
+			self.std_fitness.to_string(),
+			self.best_food.to_string(),
+			self.diversity.to_string(),
+			self.species.to_string(),
+			self.match_duration.to_string(),
+			self.wall_time.to_string()
+		]
+	}
+}
+
+fn mean(values: &[f64]) -> f64 {
+	if values.is_empty() {
+		return 0f64;
+	}
+	values.iter().sum::<f64>() / values.len() as f64
+}
+
+fn rms_distance(a: &[f32], b: &[f32]) -> f64 {
+	let squared = a.iter()
+		.zip(b)
+		.map(|(a, b)| (*a as f64 - *b as f64).powi(2))
+		.sum::<f64>();
+	(squared / a.len().max(1) as f64).sqrt()
+}
+
+fn diversity(weights: &[Vec<f32>]) -> f64 {
+	let Some(first) = weights.first() else {
+		return 0f64;
+	};
+
+	let mut centroid = vec![0f32; first.len()];
+	for genome in weights {
+		for (sum, weight) in centroid.iter_mut().zip(genome) {
+			*sum += weight / weights.len() as f32;
+		}
+	}
+
+	let distances = weights.iter()
+		.map(|genome| rms_distance(genome, &centroid))
+		.collect::<Vec<f64>>();
+	mean(&distances)
+}
+
+// Each genome joins the first species whose founder is within `threshold`, or founds a new one.
+// Returns every genome's species index, in order of founding.
+pub(crate) fn assign_species(weights: &[Vec<f32>], threshold: f64) -> Vec<usize> {
+	let mut founders: Vec<&Vec<f32>> = Vec::new();
+	weights.iter()
+		.map(|genome| {
+			match founders.iter().position(|founder| rms_distance(founder, genome) < threshold) {
+				Some(species) => species,
+				None => {
+					founders.push(genome);
+					founders.len() - 1
+				}
+			}
+		})
+		.collect()
+}
+
+fn species_count(weights: &[Vec<f32>], threshold: f64) -> usize {
+	assign_species(weights, threshold).into_iter().max().map_or(0, |species| species + 1)
+}
+
+/// File format of the metrics an experiment writes to its output directory.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MetricsFormat {
+	/// `metrics.csv`, see CsvSink
+	Csv,
+	/// `metrics.jsonl`, see JsonLinesSink
+	JsonLines
+}
+
+impl MetricsFormat {
+	/// Opens a sink appending to `metrics.csv` or `metrics.jsonl` in `directory`, so resumed runs
+	/// carry on the same file.
+	pub fn open(&self, directory: &Path) -> Result<Box<dyn MetricsSink>> {
+		let open = |name: &str| OpenOptions::new().create(true).append(true).open(directory.join(name));
+		Ok(match self {
+			MetricsFormat::Csv => {
+				let file = open("metrics.csv")?;
+				let header_written = file.metadata()?.len() > 0;
+				Box::new(CsvSink {
+					writer: BufWriter::new(file),
+					header_written
+				})
+			}
+			MetricsFormat::JsonLines => Box::new(JsonLinesSink::new(open("metrics.jsonl")?))
+		})
+	}
+}
+
+/// Receives a GenerationMetrics after every generation, to write it out or report progress.
+pub trait MetricsSink {
+	/// Handles one generation's metrics; an error stops the run.
+	fn record(&mut self, metrics: &GenerationMetrics) -> Result<()>;
+}
+
+/// One row per generation, after a header row. Flushed after every row so runs can be plotted
+/// while they are still going.
+pub struct CsvSink<W: Write> {
+	writer: BufWriter<W>,
+	header_written: bool
+}
+
+impl<W: Write> CsvSink<W> {
+	/// Writes to `writer`, starting with the header row.
+	pub fn new(writer: W) -> Self {
+		Self {
+			writer: BufWriter::new(writer),
+			header_written: false
+		}
+	}
+}
+
+impl<W: Write> MetricsSink for CsvSink<W> {
+	fn record(&mut self, metrics: &GenerationMetrics) -> Result<()> {
+		if !self.header_written {
+			writeln!(self.writer, "{}", GenerationMetrics::FIELDS.join(","))?;
+			self.header_written = true;
+		}
+		writeln!(self.writer, "{}", metrics.values().join(","))?;
+		Ok(self.writer.flush()?)
+	}
+}
+
+/// One JSON object per line and generation.
+pub struct JsonLinesSink<W: Write> {
+	writer: BufWriter<W>
+}
+
+impl<W: Write> JsonLinesSink<W> {
+	/// Writes to `writer`.
+	pub fn new(writer: W) -> Self {
+		Self {
+			writer: BufWriter::new(writer)
+		}
+	}
+}
+
+impl<W: Write> MetricsSink for JsonLinesSink<W> {
+	fn record(&mut self, metrics: &GenerationMetrics) -> Result<()> {
+		serde_json::to_writer(&mut self.writer, metrics)?;
+		writeln!(self.writer)?;
+		Ok(self.writer.flush()?)
+	}
+}
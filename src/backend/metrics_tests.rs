@@ -0,0 +1,67 @@
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod metrics_tests {
+	use std::time::Duration;
+	use crate::backend::agent::AgentStats;
+	use crate::backend::evaluation::{Aggregation, Evaluation};
+	use crate::backend::gene::Genome;
+	use crate::backend::metrics::{CsvSink, GenerationMetrics, JsonLinesSink, MetricsSink};
+
+	fn metrics() -> GenerationMetrics {
+		let evaluations = [1, 2, 3, 6].iter()
+			.map(|food| Evaluation::new(vec![AgentStats { food_eaten: *food, steps_taken: 5 }], Vec::new(), Aggregation::Mean))
+			.collect::<Vec<Evaluation>>();
+		let genome = Genome::blank(1);
+		let clone = genome.copy();
+		let other = Genome::blank(2);
+
+		GenerationMetrics::new(
+			3,
+			&evaluations.iter().collect::<Vec<&Evaluation>>(),
+			&[&genome, &clone, &other],
+			&[100, 200],
+			0.01,
+			Duration::from_millis(1500)
+		)
+	}
+
+	#[test]
+	fn summarises_generation() {
+		let metrics = metrics();
+		assert_eq!(metrics.best_fitness, 6.0);
+		assert_eq!(metrics.mean_fitness, 3.0);
+		assert_eq!(metrics.median_fitness, 2.5);
+		assert!((metrics.std_fitness - 3.5f64.sqrt()).abs() < 1e-9);
+		// the copy shares its parent's weights, so only two species
+		assert_eq!(metrics.species, 2);
+		assert!(metrics.diversity > 0.0);
+		assert_eq!(metrics.match_duration, 150.0);
+		assert_eq!(metrics.wall_time, 1.5);
+	}
+
+	#[test]
+	fn writes_csv_and_json_lines() {
+		let metrics = metrics();
+
+		let mut csv = Vec::new();
+		{
+			let mut sink = CsvSink::new(&mut csv);
+			sink.record(&metrics).unwrap();
+			sink.record(&metrics).unwrap();
+		}
+		let csv = String::from_utf8(csv).unwrap();
+		let lines = csv.lines().collect::<Vec<&str>>();
+		assert_eq!(lines.len(), 3);
+		assert!(lines[0].starts_with("generation,population,best_fitness"));
+		assert!(lines[1].starts_with("3,4,6,3,2.5"));
+
+		let mut json = Vec::new();
+		{
+			let mut sink = JsonLinesSink::new(&mut json);
+			sink.record(&metrics).unwrap();
+		}
+		let value: serde_json::Value = serde_json::from_slice(&json).unwrap();
+		assert_eq!(value["species"], 2);
+		assert_eq!(value["best_fitness"], 6.0);
+	}
+}
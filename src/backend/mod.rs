@@ -1,164 +1,550 @@
-use std::borrow::BorrowMut;
-use std::cell::RefCell;
-use std::collections::{HashMap, HashSet};
-use std::rc::Rc;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Instant;
 use cgmath::Vector2;
-use rand::{Rng, RngCore, thread_rng};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
 use crate::backend::agent::{Agent, AgentStats};
-
-mod map;
-mod entity;
-mod agent;
-mod gene;
-mod engine;
+use crate::backend::baseline::Baseline;
+
+pub(crate) mod map;
+pub(crate) mod entity;
+pub(crate) mod agent;
+pub(crate) mod archive;
+mod archive_tests;
+pub(crate) mod baseline;
+mod baseline_tests;
+pub(crate) mod behaviour;
+mod behaviour_tests;
+pub(crate) mod gene;
+pub(crate) mod network;
+pub(crate) mod policy;
+mod policy_tests;
+pub(crate) mod random;
+mod sampling;
+pub(crate) mod checkpoint;
+mod checkpoint_tests;
+pub(crate) mod config;
+pub(crate) mod engine;
+pub(crate) mod environment;
+mod environment_tests;
+pub(crate) mod error;
+pub(crate) mod evaluation;
+mod evaluation_tests;
 mod engine_tests;
-
-type Position = Vector2<i32>;
+pub(crate) mod mutation;
+mod mutation_tests;
+pub(crate) mod optimizer;
+pub(crate) mod island;
+mod island_tests;
+pub(crate) mod replay;
+mod replay_tests;
+pub(crate) mod lineage;
+mod lineage_tests;
+pub(crate) mod metrics;
+mod metrics_tests;
+pub(crate) mod sweep;
+mod sweep_tests;
+
+/// A tile on the map, x right and y down from the top-left corner.
+pub type Position = Vector2<i32>;
 type Offset = Vector2<i32>;
 
-use crate::backend::engine::{Engine, EngineConfig, MatchStats};
-use crate::backend::gene::{Genome, GenomePool};
+use crate::backend::engine::{Engine, EngineConfig, MatchResult};
+use crate::backend::behaviour::{Behaviour, NoveltyArchive, NoveltyConfig};
+use crate::backend::evaluation::{Aggregation, Evaluation, FitnessMode};
+use crate::backend::archive::{GenomeArchive, GenomeRecord};
+use crate::backend::checkpoint::Checkpoint;
+use crate::backend::config::ExperimentConfig;
+use crate::backend::error::{Error, Result};
+use crate::backend::gene::{Genome, GenomePool, RetentionPolicy};
+use crate::backend::lineage::{EvaluationRecord, Lineage};
+use crate::backend::metrics::{GenerationMetrics, MetricsFormat, MetricsSink};
+use crate::backend::mutation::MutationOperator;
+use crate::backend::network::NetworkShape;
+use crate::backend::optimizer::{Optimizer, OptimizerKind};
+use crate::backend::policy::PolicyMode;
+use crate::backend::random::rng;
+use crate::backend::replay::Replay;
+
+/// How an Orchestrator selects and evaluates genomes; the `[evolution]` table of an ExperimentConfig.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OrchestratorConfig {
+	/// matches played by every genome each generation; its fitness aggregates over all of them
+	pub trials: usize,
+	/// how a genome's trials reduce to its food
+	pub aggregation: Aggregation,
+	/// action selection while evolving
+	pub training_policy: PolicyMode,
+	/// action selection when benchmarking genomes outside of selection
+	pub evaluation_policy: PolicyMode,
+	/// what picks each generation's genomes
+	pub optimizer: OptimizerKind,
+	/// what the optimizer selects on
+	pub fitness_mode: FitnessMode,
+	/// novelty scoring, used unless fitness_mode is Food
+	pub novelty: NoveltyConfig,
+	/// applied in order to every mutant's weights by the genetic optimizer
+	pub mutation: Vec<MutationOperator>,
+	/// nodes in the networks' hidden layer; the input layer follows EngineConfig::vision_radius
+	pub hidden_nodes: usize,
+	/// worker threads matches are spread over; defaults to the available parallelism
+	pub threads: usize,
+	/// which genomes stay in memory between generations; the rest go to the archive in output_dir
+	pub retention: RetentionPolicy,
+	/// per-generation metrics file written to output_dir
+	pub metrics_format: MetricsFormat,
+	/// RMS weight distance under which two genomes count as the same species in the metrics
+	pub species_threshold: f64,
+	/// scripted agents added to every match next to the population; they compete for food but are
+	/// never evaluated or evolved
+	pub baselines: Vec<Baseline>,
+	/// where run artefacts (e.g. the MAP-Elites grid) are written at the end of a run, if anywhere
+	#[serde(skip)]
+	pub output_dir: Option<PathBuf>
+}
+
+impl Default for OrchestratorConfig {
+	fn default() -> Self {
+		let threads = thread::available_parallelism()
+			.map(|count| count.get())
+			.unwrap_or(1);
+
+		Self {
+			trials: 4,
+			aggregation: Aggregation::Mean,
+			training_policy: PolicyMode::Stochastic,
+			evaluation_policy: PolicyMode::Greedy,
+			optimizer: OptimizerKind::Genetic { elites: 4, randoms: 2 },
+			fitness_mode: FitnessMode::Food,
+			novelty: NoveltyConfig::default(),
+			mutation: MutationOperator::defaults(),
+			hidden_nodes: network::HIDDEN_NODES,
+			threads,
+			retention: RetentionPolicy::default(),
+			metrics_format: MetricsFormat::Csv,
+			species_threshold: 0.05,
+			baselines: Vec::new(),
+			output_dir: None
+		}
+	}
+}
 
+/// Runs evolution: asks the optimizer for a population each generation, plays it on Engines
+/// spread over worker threads, and reports the evaluations back to the optimizer.
 pub struct Orchestrator {
-	engine: Engine,
+	engine_config: EngineConfig,
+	/// the settings the orchestrator was built with
+	pub config: OrchestratorConfig,
+	optimizer: Box<dyn Optimizer>,
+	/// behaviours found novel so far
+	pub novelty_archive: NoveltyArchive,
+	/// every genome evaluated so far and its parents
+	pub lineage: Lineage,
+	metrics_sinks: Vec<Box<dyn MetricsSink>>,
+	latest_metrics: Option<GenerationMetrics>,
+	// the generator is reseeded with this at every generation boundary, see Orchestrator::checkpoint
+	rng_seed: u64,
+	/// the genomes the optimizer and matches draw on
 	pub genepool: GenomePool
 }
 
 impl Orchestrator {
-	pub fn new() -> Self {
-
-		// let mut brain = Genome::blank(123);
-		// brain.test();
-		// println!("{:?}", brain);
+	/// An orchestrator with the default world and settings, writing nothing to disk.
+	pub fn new() -> Result<Self> {
+		Orchestrator::with_config(EngineConfig::default(), OrchestratorConfig::default())
+	}
 
-		let config = EngineConfig {
-			food_spread: 2.5f64,
-			size: [100f64, 100f64],
-			round_max: 500,
-			agent_count: 10
+	/// Validates both configs and opens the genome archive and metrics file in the output directory.
+	pub fn with_config(engine_config: EngineConfig, config: OrchestratorConfig) -> Result<Self> {
+		engine_config.validate()?;
+		config.validate(&engine_config)?;
+		let shape = NetworkShape { inputs: engine_config.vision_tiles(), hidden: config.hidden_nodes };
+		let optimizer = config.optimizer.build(engine_config.agent_count, &config.mutation, shape);
+		let mut metrics_sinks = Vec::new();
+		let genepool = match &config.output_dir {
+			Some(output_dir) => {
+				std::fs::create_dir_all(output_dir)?;
+				let archive = GenomeArchive::open(&output_dir.join("genomes.jsonl"))?;
+				metrics_sinks.push(config.metrics_format.open(output_dir)?);
+				GenomePool::with_archive(archive)
+			}
+			None => GenomePool::new()
 		};
 
-		let engine = Engine::new(config);
-		let genepool = GenomePool::new();
-
-		Self {
-			engine,
+		Ok(Self {
+			engine_config,
+			novelty_archive: NoveltyArchive::new(config.novelty),
+			config,
+			optimizer,
+			lineage: Lineage::new(),
+			metrics_sinks,
+			latest_metrics: None,
+			rng_seed: random::advance(),
 			genepool
-		}
+		})
 	}
 
-	pub fn get_agent_position(&self, seen_positions: &mut HashSet<Position> ) -> Position {
-		let mut position_found : bool = false;
-		let mut candidate_position: Position = Vector2 { x: 0, y: 0 };
-		while !position_found {
-			let new_x = thread_rng().gen_range(0..=(self.engine.config.size[0] as i32));
-			let new_y = thread_rng().gen_range(0..=(self.engine.config.size[1] as i32));
-			candidate_position = Position::new(new_x, new_y);
+	/// Runs `generations` generations from the start, then benchmarks and exports the final population.
+	pub fn start_matches(&mut self, generations: usize) -> Result<()> {
+		let mut population = Vec::new();
 
-			position_found = seen_positions.insert(candidate_position);
+		for i in 0..generations {
+			let evaluations = self.run_generation(i)?;
+			population = Orchestrator::population(&evaluations);
 		}
-		candidate_position
+
+		if !population.is_empty() {
+			self.finish(&population)?;
+		}
+		Ok(())
 	}
 
-	pub fn start_matches(&mut self) {
-		let mut round:  usize = 0;
+	/// Runs generations `start..experiment.generations` after `population`, the last one played,
+	/// checkpointing into the experiment's output directory. The resolved experiment is written
+	/// alongside as config.toml. Returns the final population.
+	pub fn evolve(&mut self, experiment: &ExperimentConfig, start: usize, mut population: Vec<u64>) -> Result<Vec<u64>> {
+		if let Some(output_dir) = &experiment.output_dir {
+			experiment.save(&output_dir.join("config.toml"))?;
+		}
+
+		let interval = experiment.checkpoint_interval.max(1);
+		for generation in start..experiment.generations {
+			let evaluations = self.run_generation(generation)?;
+			population = Orchestrator::population(&evaluations);
+
+			let completed = generation + 1;
+			if let Some(output_dir) = &experiment.output_dir {
+				if completed.is_multiple_of(interval) || completed == experiment.generations {
+					self.checkpoint(experiment, completed, &population)
+						.save(&output_dir.join("checkpoint.json"))?;
+				}
+			}
+		}
+		Ok(population)
+	}
 
-		let mut initial_agents: HashMap<u64, Agent> = HashMap::new();
-		let mut seen_positions = HashSet::new();
-		for idx in 0..self.engine.config.agent_count {
-			let agent_id = thread_rng().next_u64();
-			let genome = Rc::new(RefCell::new(Genome::blank(agent_id)));
-			self.genepool.add_genome(agent_id, genome.clone());
+	/// Genome ids of a generation's evaluations, in a stable order.
+	pub fn population(evaluations: &HashMap<u64, Evaluation>) -> Vec<u64> {
+		let mut population = evaluations.keys().cloned().collect::<Vec<u64>>();
+		population.sort();
+		population
+	}
 
-			let new_agent = Agent {
-				id: agent_id,
-				position: self.get_agent_position(&mut seen_positions),
-				genome: genome.clone(),
-				current_sense: None
+	/// Benchmarks the final population and writes the run's artefacts: the best genome, a replay
+	/// of the population and whatever `export` writes. Returns the best genome's id and benchmark.
+	pub fn finish(&self, population: &[u64]) -> Result<(u64, Evaluation)> {
+		let benchmark = self.benchmark(population, self.config.trials)?;
+		let (best_id, best) = benchmark.iter()
+			.max_by(|(_, a), (_, b)| a.fitness.total_cmp(&b.fitness))
+			.ok_or(Error::EmptyPopulation)?;
+
+		if let Some(output_dir) = &self.config.output_dir {
+			let record = GenomeRecord {
+				vision_radius: Some(self.engine_config.vision_radius),
+				..GenomeRecord::from_genome(self.genepool.get_genome(*best_id)?)
 			};
-			initial_agents.insert(agent_id, new_agent);
-		}
-		let mut match_stats = self.engine.play_match(initial_agents, round);
-
-		for i in 0..100 {
-			round += 1;
-			let mut agents: HashMap<u64, Agent> = HashMap::new();
-			seen_positions.clear();
-			println!("{}", i);
-
-			let new_genes = self.select_new_genes(&match_stats);
-
-			for (id, gene) in new_genes.iter() {
-				let new_agent = Agent {
-					id: *id,
-					position: self.get_agent_position(&mut seen_positions),
-					genome: gene.clone(),
-					current_sense: None
-				};
-				agents.insert(gene.borrow().id, new_agent);
-			}
-			match_stats = self.engine.play_match(agents, round);
-			// matches.push(match_stats);
+			record.save(&output_dir.join("best_genome.json"))?;
+			self.record_match(population, self.config.evaluation_policy)?
+				.save(&output_dir.join("replay.jsonl"))?;
 		}
+		self.export()?;
+		Ok((*best_id, best.clone()))
 	}
 
-	pub fn select_new_genes(&mut self, match_stats: &MatchStats) -> HashMap<u64, Rc<RefCell<Genome>>> {
-		let mut new_genes: HashMap<u64, Rc<RefCell<Genome>>> = HashMap::new();
+	/// Snapshot of the run after `generation` generations, `population` being the last one. The
+	/// generator state it records is the seed of the last generation boundary, so taking a
+	/// checkpoint draws nothing and leaves the run as it would have been without one.
+	pub fn checkpoint(&self, experiment: &ExperimentConfig, generation: usize, population: &[u64]) -> Checkpoint {
+		Checkpoint {
+			experiment: experiment.clone(),
+			generation,
+			population: population.to_vec(),
+			genomes: self.genepool.genomes().map(GenomeRecord::from_genome).collect(),
+			optimizer: self.optimizer.state(),
+			novelty_archive: self.novelty_archive.entries().to_vec(),
+			lineage: self.lineage.nodes().cloned().collect(),
+			rng_seed: self.rng_seed
+		}
+	}
 
-		// println!("{:?}", match_stats);
+	/// Rebuilds the orchestrator a checkpoint was taken from. Genome history and the archive index
+	/// start afresh; everything selection depends on is restored.
+	pub fn resume(checkpoint: Checkpoint) -> Result<Self> {
+		let experiment = &checkpoint.experiment;
+		let mut orchestrator = Orchestrator::with_config(experiment.engine.clone(), experiment.orchestrator_config())?;
+		orchestrator.genepool.set_generation(checkpoint.generation.saturating_sub(1));
+		let shape = NetworkShape { inputs: experiment.engine.vision_tiles(), hidden: experiment.evolution.hidden_nodes };
+		for record in &checkpoint.genomes {
+			orchestrator.genepool.add_genome(record.to_genome(shape)?);
+		}
+		orchestrator.optimizer.restore(checkpoint.optimizer)?;
+		orchestrator.novelty_archive.restore(checkpoint.novelty_archive);
+		orchestrator.lineage = Lineage::from_nodes(checkpoint.lineage);
+		random::seed(checkpoint.rng_seed);
+		orchestrator.rng_seed = checkpoint.rng_seed;
+		Ok(orchestrator)
+	}
 
-		let mut agent_score = match_stats.agent_stats
-			.iter()
-			.collect::<Vec<(&u64, &AgentStats)>>();
+	/// Asks the optimizer for a population, evaluates it and reports the results back.
+	pub fn run_generation(&mut self, generation: usize) -> Result<HashMap<u64, Evaluation>> {
+		let started = Instant::now();
+		self.genepool.set_generation(generation);
+		let population = self.optimizer.ask(&mut self.genepool)?;
+		for id in &population {
+			self.lineage.record_birth(self.genepool.get_genome(*id)?, generation);
+		}
 
-		println!("{}", agent_score.iter().map(|(idx, stats)| stats.food_eaten).max().unwrap());
+		let (evaluations, match_results) = self.evaluate_matches(&population, generation)?;
+		for (id, evaluation) in &evaluations {
+			self.genepool.record_evaluation(*id, generation, evaluation);
+			self.lineage.record_evaluation(*id, EvaluationRecord {
+				generation,
+				fitness: evaluation.fitness,
+				food: evaluation.food
+			});
+		}
 
-		agent_score.sort_by_key(|(id, stats)| stats.food_eaten);
-		let best = agent_score
-			.iter()
-			.rev()
-			.take(4)
-			.map(|(id, stats)| (*id, *stats))
-			.collect::<Vec<(&u64, &AgentStats)>>();
+		let genomes = population.iter()
+			.map(|id| self.genepool.get_genome(*id))
+			.collect::<Result<Vec<&Genome>>>()?;
+		let durations = match_results.iter()
+			.map(|result| result.duration)
+			.collect::<Vec<usize>>();
+		let metrics = GenerationMetrics::new(
+			generation,
+			&evaluations.values().collect::<Vec<&Evaluation>>(),
+			&genomes,
+			&durations,
+			self.config.species_threshold,
+			started.elapsed()
+		);
+		for sink in &mut self.metrics_sinks {
+			sink.record(&metrics)?;
+		}
+		self.latest_metrics = Some(metrics);
 
-		// println!("{:?}", best);
+		self.optimizer.tell(&self.genepool, &evaluations)?;
+		self.genepool.retain(&population, self.config.retention, &self.lineage)?;
+		self.rng_seed = random::advance();
+		Ok(evaluations)
+	}
 
-		for (id, stats) in &match_stats.agent_stats {
-			let mut gene = (self.genepool.get_genome(*id)).as_ref().borrow_mut();
-			gene.stats.food_eaten = 0;
-			gene.stats.cumulative_food_eaten += stats.food_eaten;
-			gene.stats.steps_taken = 0;
+	/// Adds genomes evaluated elsewhere (e.g. on another island) to the pool and offers them to the optimizer.
+	pub fn immigrate(&mut self, migrants: Vec<(Genome, Evaluation)>, generation: usize) -> Result<()> {
+		let mut evaluations = HashMap::new();
+		for (genome, evaluation) in migrants {
+			self.lineage.record_birth(&genome, generation);
+			self.genepool.add_genome(genome.clone());
+			self.genepool.record_evaluation(genome.id, generation, &evaluation);
+			evaluations.insert(genome.id, evaluation);
 		}
+		self.optimizer.immigrate(&self.genepool, &evaluations)
+	}
+
+	/// Metrics of the last generation run, if any.
+	pub fn latest_metrics(&self) -> Option<&GenerationMetrics> {
+		self.latest_metrics.as_ref()
+	}
 
-		for (id, score) in best.iter() {
-			let gene = self.genepool.get_genome(**id);
-			new_genes.insert(gene.borrow().id, gene.clone());
+	/// Sends every generation's metrics to `sink` as well as any configured metrics file.
+	pub fn add_metrics_sink(&mut self, sink: Box<dyn MetricsSink>) {
+		self.metrics_sinks.push(sink);
+	}
+
+	/// Writes the optimizer's state and the lineage into the output directory, if there is one.
+	pub fn export(&self) -> Result<()> {
+		if let Some(output_dir) = &self.config.output_dir {
+			std::fs::create_dir_all(output_dir)?;
+			self.optimizer.export(output_dir)?;
+			self.lineage.export(output_dir)?;
 		}
+		Ok(())
+	}
 
-		for (id, score) in best.iter() {
-			let gene = self.genepool.get_genome(**id);
+	/// Each agent gets its own copy of the genome, so matches can run on other threads
+	/// without touching the pool. The configured baselines join them under ids counting down
+	/// from u64::MAX. Fails if a genome's network doesn't take this engine's vision as input.
+	pub fn build_agents(&self, genome_ids: &[u64]) -> Result<HashMap<u64, Agent>> {
+		let mut positions = self.engine_config.spawn_positions(genome_ids.len() + self.config.baselines.len())?;
+		let baseline_positions = positions.split_off(genome_ids.len());
+		let mut agents = genome_ids.iter()
+			.zip(positions)
+			.map(|(id, position)| {
+				let genome = self.genepool.get_genome(*id)?;
+				let tiles = self.engine_config.vision_tiles();
+				if genome.shape().inputs != tiles {
+					return Err(Error::InvalidConfig(format!(
+						"genome {} takes {} inputs but engine.vision_radius {} gives {} tiles",
+						id,
+						genome.shape().inputs,
+						self.engine_config.vision_radius,
+						tiles
+					)));
+				}
+				Ok((*id, Agent::new(*id, position, genome.clone())))
+			})
+			.collect::<Result<HashMap<u64, Agent>>>()?;
+		for (idx, (baseline, position)) in self.config.baselines.iter().zip(baseline_positions).enumerate() {
+			let id = u64::MAX - idx as u64;
+			agents.insert(id, Agent::scripted(id, position, Box::new(*baseline)));
+		}
+		Ok(agents)
+	}
+
+	/// Plays `trials` independent matches of the whole population, each with fresh spawn
+	/// positions and food, and aggregates every genome's results across them.
+	pub fn evaluate(&mut self, genome_ids: &[u64], round: usize) -> Result<HashMap<u64, Evaluation>> {
+		Ok(self.evaluate_matches(genome_ids, round)?.0)
+	}
 
-			let mut mutated_gene = gene.borrow().copy();
-			mutated_gene.mutate();
-			mutated_gene.stats.food_eaten = 0;
-			mutated_gene.stats.cumulative_food_eaten += score.food_eaten;
-			mutated_gene.stats.parent = Some(gene.borrow().id.clone());
-			mutated_gene.stats.generation += 1;
+	fn evaluate_matches(&mut self, genome_ids: &[u64], round: usize) -> Result<(HashMap<u64, Evaluation>, Vec<MatchResult>)> {
+		let match_results = self.play_trials(genome_ids, self.config.trials, round, self.config.training_policy)?;
+		let mut evaluations = Orchestrator::group_trials(&match_results, genome_ids, self.config.aggregation);
+		self.score_fitness(&mut evaluations);
+		Ok((evaluations, match_results))
+	}
 
-			new_genes.insert(mutated_gene.id, Rc::new(RefCell::new(mutated_gene)));
+	// Rewrites every evaluation's fitness according to the configured FitnessMode.
+	fn score_fitness(&mut self, evaluations: &mut HashMap<u64, Evaluation>) {
+		if self.config.fitness_mode == FitnessMode::Food {
+			return;
+		}
+
+		let ids = evaluations.keys().cloned().collect::<Vec<u64>>();
+		let descriptors = ids.iter()
+			.map(|id| evaluations[id].descriptor())
+			.collect::<Vec<Vec<f64>>>();
+		let novelty = self.novelty_archive.score(&descriptors);
+
+		let max_food = evaluations.values().map(|evaluation| evaluation.food).fold(0f64, f64::max);
+		let max_novelty = novelty.iter().cloned().fold(0f64, f64::max);
+		for (id, novelty) in ids.iter().zip(novelty) {
+			let evaluation = evaluations.get_mut(id).unwrap();
+			evaluation.novelty = novelty;
+			evaluation.fitness = match self.config.fitness_mode {
+				FitnessMode::Food => evaluation.food,
+				FitnessMode::Novelty => novelty,
+				FitnessMode::Combined { novelty_weight } => {
+					let food_score = if max_food > 0f64 { evaluation.food / max_food } else { 0f64 };
+					let novelty_score = if max_novelty > 0f64 { novelty / max_novelty } else { 0f64 };
+					(1f64 - novelty_weight) * food_score + novelty_weight * novelty_score
+				}
+			};
 		}
+	}
 
-		for idx in 0..2 {
-			let gene_id = thread_rng().next_u64();
-			let gene = Rc::new(RefCell::new(Genome::blank(gene_id)));
+	/// Scores genomes with the evaluation policy without feeding the results back into the pool.
+	pub fn benchmark(&self, genome_ids: &[u64], trials: usize) -> Result<HashMap<u64, Evaluation>> {
+		let match_results = self.play_trials(genome_ids, trials, 0, self.config.evaluation_policy)?;
+		Ok(Orchestrator::group_trials(&match_results, genome_ids, self.config.aggregation))
+	}
 
-			new_genes.insert(gene_id, gene);
+	/// Plays `trials` matches of engine_config.agent_count agents all following `baseline`, as a
+	/// reference for `benchmark`. Every agent's match counts as one trial of the evaluation.
+	pub fn benchmark_baseline(&self, baseline: Baseline, trials: usize) -> Result<Evaluation> {
+		let matches = (0..trials.max(1))
+			.map(|_| {
+				let positions = self.engine_config.spawn_positions(self.engine_config.agent_count)?;
+				Ok(positions.into_iter()
+					.enumerate()
+					.map(|(id, position)| (id as u64, Agent::scripted(id as u64, position, Box::new(baseline))))
+					.collect())
+			})
+			.collect::<Result<Vec<HashMap<u64, Agent>>>>()?;
+		let match_results = self.play_matches(matches, 0, self.config.evaluation_policy)?;
+
+		let (mut trials, mut behaviours) = (Vec::new(), Vec::new());
+		for result in &match_results {
+			let mut ids = result.agent_stats.keys().cloned().collect::<Vec<u64>>();
+			ids.sort();
+			for id in ids {
+				trials.push(result.agent_stats[&id]);
+				behaviours.push(result.behaviours[&id].clone());
+			}
 		}
+		Ok(Evaluation::new(trials, behaviours, self.config.aggregation))
+	}
+
+	fn play_trials(&self, genome_ids: &[u64], trials: usize, round: usize, policy: PolicyMode) -> Result<Vec<MatchResult>> {
+		let matches = (0..trials.max(1))
+			.map(|_| self.build_agents(genome_ids))
+			.collect::<Result<Vec<HashMap<u64, Agent>>>>()?;
+		self.play_matches(matches, round, policy)
+	}
+
+	/// An engine with a match between `genome_ids` set up but not yet played.
+	pub fn begin_match(&self, genome_ids: &[u64], policy: PolicyMode) -> Result<Engine> {
+		let mut engine = Engine::new(self.engine_config.clone())?;
+		engine.policy = policy;
+		engine.begin_match(self.build_agents(genome_ids)?, 0);
+		Ok(engine)
+	}
 
-		for (id, gene) in &new_genes {
-			self.genepool.add_genome(*id, gene.clone());
+	/// Plays a single match on this thread and returns its step-by-step trace.
+	pub fn record_match(&self, genome_ids: &[u64], policy: PolicyMode) -> Result<Replay> {
+		let mut engine = Engine::new(self.engine_config.clone())?;
+		engine.policy = policy;
+		engine.record_replay = true;
+		engine.play_match(self.build_agents(genome_ids)?, 0)?;
+		engine.replay.take().ok_or(Error::NotRecorded)
+	}
+
+	/// Plays every match on its own Engine, spread over up to `threads` worker threads.
+	/// Results are returned in the same order as `matches`.
+	pub fn play_matches(&self, matches: Vec<HashMap<u64, Agent>>, round: usize, policy: PolicyMode) -> Result<Vec<MatchResult>> {
+		let match_count = matches.len();
+		// every match draws from its own seed, so results don't depend on which worker plays it
+		let queue = Mutex::new(matches.into_iter()
+			.enumerate()
+			.map(|(idx, agents)| (idx, rng().next_u64(), agents))
+			.collect::<Vec<(usize, u64, HashMap<u64, Agent>)>>());
+		let results = Mutex::new(Vec::with_capacity(match_count));
+		let engines = (0..self.config.threads.min(match_count))
+			.map(|_| Engine::new(self.engine_config.clone()))
+			.collect::<Result<Vec<Engine>>>()?;
+
+		thread::scope(|scope| {
+			for mut engine in engines {
+				let (queue, results) = (&queue, &results);
+				scope.spawn(move || {
+					engine.policy = policy;
+					loop {
+						let next_match = queue.lock().unwrap().pop();
+						let Some((idx, seed, agents)) = next_match else {
+							break;
+						};
+						random::reseed(seed);
+						let match_result = engine.play_match(agents, round);
+						results.lock().unwrap().push((idx, match_result));
+					}
+				});
+			}
+		});
+
+		let mut results = results.into_inner().unwrap();
+		results.sort_by_key(|(idx, _)| *idx);
+		results.into_iter()
+			.map(|(_, match_result)| match_result)
+			.collect()
+	}
+
+	// Groups the per-match results by genome into one Evaluation each, leaving out any baselines.
+	fn group_trials(match_results: &[MatchResult], genome_ids: &[u64], aggregation: Aggregation) -> HashMap<u64, Evaluation> {
+		let mut trials: HashMap<u64, (Vec<AgentStats>, Vec<Behaviour>)> = HashMap::new();
+		for result in match_results {
+			for id in genome_ids {
+				let (genome_trials, behaviours) = trials.entry(*id).or_default();
+				genome_trials.push(result.agent_stats[id]);
+				behaviours.push(result.behaviours[id].clone());
+			}
 		}
 
-		new_genes
+		trials.into_iter()
+			.map(|(id, (trials, behaviours))| (id, Evaluation::new(trials, behaviours, aggregation)))
+			.collect()
 	}
 }
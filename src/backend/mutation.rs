@@ -0,0 +1,77 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use rand_distr::StandardNormal;
+
+/// A single step of the mutation pipeline. Genome::mutate applies a list of these in order to the
+/// flattened network weights and the genome's per-weight step sizes; every `probability` is the
+/// per-weight chance of the operator firing.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum MutationOperator {
+	/// add zero-mean gaussian noise with standard deviation `sigma`
+	Gaussian { sigma: f32, probability: f64 },
+	/// replace the weight with a fresh draw from U(-range, range)
+	Reset { range: f32, probability: f64 },
+	/// negate the weight
+	SignFlip { probability: f64 },
+	/// clamp every weight into [min, max]
+	Clip { min: f32, max: f32 },
+	/// uncorrelated self-adaptive ES mutation: every step size is first perturbed log-normally,
+	/// then used as the standard deviation of that weight's gaussian noise
+	SelfAdaptive { min_step: f32 }
+}
+
+impl MutationOperator {
+	/// Mutates `weights` in place; only SelfAdaptive reads or changes `step_sizes`.
+	pub fn apply<R: Rng>(&self, weights: &mut [f32], step_sizes: &mut [f32], rng: &mut R) {
+		match *self {
+			MutationOperator::Gaussian { sigma, probability } => {
+				for weight in weights.iter_mut() {
+					if rng.gen_bool(probability) {
+						*weight += sigma * rng.sample::<f32, _>(StandardNormal);
+					}
+				}
+			}
+			MutationOperator::Reset { range, probability } => {
+				for weight in weights.iter_mut() {
+					if rng.gen_bool(probability) {
+						*weight = rng.gen_range(-range..=range);
+					}
+				}
+			}
+			MutationOperator::SignFlip { probability } => {
+				for weight in weights.iter_mut() {
+					if rng.gen_bool(probability) {
+						*weight = -*weight;
+					}
+				}
+			}
+			MutationOperator::Clip { min, max } => {
+				for weight in weights.iter_mut() {
+					*weight = weight.clamp(min, max);
+				}
+			}
+			MutationOperator::SelfAdaptive { min_step } => {
+				// learning rates from Schwefel: a shared global factor plus one per weight
+				let n = weights.len() as f32;
+				let global_rate = 1f32 / (2f32 * n).sqrt();
+				let local_rate = 1f32 / (2f32 * n.sqrt()).sqrt();
+				let global_noise = global_rate * rng.sample::<f32, _>(StandardNormal);
+
+				for (weight, step_size) in weights.iter_mut().zip(step_sizes.iter_mut()) {
+					let local_noise = local_rate * rng.sample::<f32, _>(StandardNormal);
+					*step_size = (*step_size * (global_noise + local_noise).exp()).max(min_step);
+					*weight += *step_size * rng.sample::<f32, _>(StandardNormal);
+				}
+			}
+		}
+	}
+
+	/// OrchestratorConfig's default pipeline: self-adaptive mutation, then a clip to [-5, 5].
+	pub fn defaults() -> Vec<MutationOperator> {
+		vec![
+			MutationOperator::SelfAdaptive { min_step: 0.001 },
+			MutationOperator::Clip { min: -5.0, max: 5.0 }
+		]
+	}
+}
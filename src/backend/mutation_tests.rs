@@ -0,0 +1,52 @@
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod mutation_tests {
+	use rand::thread_rng;
+	use crate::backend::mutation::MutationOperator;
+
+	#[test]
+	fn zero_probability_is_noop() {
+		let mut weights = vec![0.5f32, -1.0, 2.0];
+		let operators = [
+			MutationOperator::Gaussian { sigma: 1.0, probability: 0.0 },
+			MutationOperator::Reset { range: 1.0, probability: 0.0 },
+			MutationOperator::SignFlip { probability: 0.0 }
+		];
+		let mut step_sizes = vec![0.1f32; weights.len()];
+		for operator in operators.iter() {
+			operator.apply(&mut weights, &mut step_sizes, &mut thread_rng());
+		}
+		assert_eq!(weights, vec![0.5f32, -1.0, 2.0]);
+	}
+
+	#[test]
+	fn sign_flip_and_clip() {
+		let mut weights = vec![0.5f32, -1.0, 8.0];
+		let mut step_sizes = vec![0.1f32; weights.len()];
+		MutationOperator::SignFlip { probability: 1.0 }.apply(&mut weights, &mut step_sizes, &mut thread_rng());
+		assert_eq!(weights, vec![-0.5f32, 1.0, -8.0]);
+
+		MutationOperator::Clip { min: -2.0, max: 2.0 }.apply(&mut weights, &mut step_sizes, &mut thread_rng());
+		assert_eq!(weights, vec![-0.5f32, 1.0, -2.0]);
+	}
+
+	#[test]
+	fn gaussian_is_zero_mean() {
+		let mut weights = vec![0f32; 20000];
+		let mut step_sizes = vec![0.1f32; weights.len()];
+		MutationOperator::Gaussian { sigma: 0.5, probability: 1.0 }.apply(&mut weights, &mut step_sizes, &mut thread_rng());
+		let mean = weights.iter().sum::<f32>() / weights.len() as f32;
+		assert!(mean.abs() < 0.05);
+	}
+
+	#[test]
+	fn self_adaptive_updates_step_sizes() {
+		let mut weights = vec![0f32; 100];
+		let mut step_sizes = vec![0.1f32; weights.len()];
+		MutationOperator::SelfAdaptive { min_step: 0.05 }.apply(&mut weights, &mut step_sizes, &mut thread_rng());
+
+		assert!(step_sizes.iter().any(|step_size| *step_size != 0.1));
+		assert!(step_sizes.iter().all(|step_size| *step_size >= 0.05));
+		assert!(weights.iter().any(|weight| *weight != 0.0));
+	}
+}
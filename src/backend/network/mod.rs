@@ -0,0 +1,66 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use crate::backend::engine::EngineConfig;
+use crate::backend::random::rng;
+
+#[cfg(feature = "torch")]
+mod torch;
+#[cfg(not(feature = "torch"))]
+mod native;
+mod network_tests;
+
+#[cfg(feature = "torch")]
+pub use crate::backend::network::torch::Network;
+#[cfg(not(feature = "torch"))]
+pub use crate::backend::network::native::Network;
+
+// Both backends share this architecture and flatten their weights in the same order
+// (layer1.weight, layer1.bias, final.weight, final.bias) so genomes are interchangeable.
+// The input and hidden layer sizes are configurable, see NetworkShape; these are the defaults.
+pub const INPUT_NODES: usize = (EngineConfig::DEFAULT_VISION_RADIUS * 2 + 1).pow(2);
+pub const HIDDEN_NODES: usize = 32;
+pub const OUTPUT_NODES: usize = 4;
+
+/// Layer sizes of a genome's network: inputs, one hidden layer, and OUTPUT_NODES outputs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct NetworkShape {
+	/// one per tile an agent can see, see EngineConfig::vision_tiles
+	pub inputs: usize,
+	/// see OrchestratorConfig::hidden_nodes
+	pub hidden: usize
+}
+
+impl NetworkShape {
+	/// The shape for the default vision radius and hidden layer.
+	pub const DEFAULT: NetworkShape = NetworkShape { inputs: INPUT_NODES, hidden: HIDDEN_NODES };
+	/// the most weights a configured network may have, which bounds the memory of a population
+	pub const MAX_WEIGHTS: usize = 1 << 20;
+
+	/// Weights and biases across both layers, as Genome::weights lays them out.
+	pub const fn weight_count(&self) -> usize {
+		(self.inputs * self.hidden) + self.hidden + (self.hidden * OUTPUT_NODES) + OUTPUT_NODES
+	}
+}
+
+// Fresh weights in the shared layout, drawn from the simulation's generator. Matches torch's default
+// linear initialisation: U(-1/sqrt(fan_in), 1/sqrt(fan_in)).
+pub(crate) fn initial_weights(shape: NetworkShape) -> Vec<f32> {
+	let mut rng = rng();
+	let layer1_bound = 1.0 / (shape.inputs as f32).sqrt();
+	let final_bound = 1.0 / (shape.hidden as f32).sqrt();
+
+	// layer1.weight and layer1.bias come first
+	let final_weight = shape.inputs * shape.hidden + shape.hidden;
+	(0..shape.weight_count())
+		.map(|idx| {
+			let bound = if idx < final_weight { layer1_bound } else { final_bound };
+			rng.gen_range(-bound..bound)
+		})
+		.collect()
+}
+
+impl Default for NetworkShape {
+	fn default() -> Self {
+		NetworkShape::DEFAULT
+	}
+}
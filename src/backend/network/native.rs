@@ -0,0 +1,83 @@
+use crate::backend::network::{initial_weights, NetworkShape, OUTPUT_NODES};
+
+// Pure-Rust counterpart of the libtorch network: a flat weight vector laid out as
+// layer1.weight [HIDDEN, INPUT], layer1.bias [HIDDEN], final.weight [OUTPUT, HIDDEN], final.bias [OUTPUT].
+#[derive(Debug, Clone)]
+pub struct Network {
+	shape: NetworkShape,
+	weights: Vec<f32>
+}
+
+impl Network {
+
+	pub fn new(shape: NetworkShape) -> Self {
+		Network {
+			shape,
+			weights: initial_weights(shape)
+		}
+	}
+
+	// offsets of each layer's parameters in the flat weight vector
+	fn layer1_bias(shape: NetworkShape) -> usize {
+		shape.inputs * shape.hidden
+	}
+
+	fn final_weight(shape: NetworkShape) -> usize {
+		Network::layer1_bias(shape) + shape.hidden
+	}
+
+	fn final_bias(shape: NetworkShape) -> usize {
+		Network::final_weight(shape) + shape.hidden * OUTPUT_NODES
+	}
+
+	pub fn shape(&self) -> NetworkShape {
+		self.shape
+	}
+
+	pub fn weights(&self) -> Vec<f32> {
+		self.weights.clone()
+	}
+
+	pub fn set_weights(&mut self, weights: &[f32]) {
+		self.weights.copy_from_slice(weights);
+	}
+
+	pub fn forward_batch(networks: &[&Network], inputs: &[f32]) -> Vec<f32> {
+		let Some(first) = networks.first() else {
+			return Vec::new();
+		};
+		networks.iter()
+			.zip(inputs.chunks(first.shape.inputs))
+			.flat_map(|(network, input)| network.forward(input))
+			.collect()
+	}
+
+	fn forward(&self, input: &[f32]) -> [f32; OUTPUT_NODES] {
+		let shape = self.shape;
+		let mut hidden = vec![0f32; shape.hidden];
+		for (node, value) in hidden.iter_mut().enumerate() {
+			let row = &self.weights[node * shape.inputs..][..shape.inputs];
+			let sum: f32 = row.iter().zip(input).map(|(w, x)| w * x).sum();
+			*value = (sum + self.weights[Network::layer1_bias(shape) + node]).tanh();
+		}
+
+		let mut output = [0f32; OUTPUT_NODES];
+		for (node, value) in output.iter_mut().enumerate() {
+			let row = &self.weights[Network::final_weight(shape) + node * shape.hidden..][..shape.hidden];
+			let sum: f32 = row.iter().zip(hidden.iter()).map(|(w, x)| w * x).sum();
+			*value = sum + self.weights[Network::final_bias(shape) + node];
+		}
+
+		// softmax, shifted by the max logit for numerical stability
+		let max = output.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+		let mut total = 0f32;
+		for value in output.iter_mut() {
+			*value = (*value - max).exp();
+			total += *value;
+		}
+		for value in output.iter_mut() {
+			*value /= total;
+		}
+		output
+	}
+}
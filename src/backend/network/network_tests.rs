@@ -0,0 +1,42 @@
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod network_tests {
+	use crate::backend::network::{Network, NetworkShape, OUTPUT_NODES};
+
+	#[test]
+	fn weights_roundtrip() {
+		let network = Network::new(NetworkShape::DEFAULT);
+		let weights = network.weights();
+		assert_eq!(weights.len(), NetworkShape::DEFAULT.weight_count());
+
+		let mut other = Network::new(NetworkShape::DEFAULT);
+		other.set_weights(&weights);
+		assert_eq!(other.weights(), weights);
+	}
+
+	#[test]
+	fn forward_batch_probabilities() {
+		// a smaller vision and hidden layer than the defaults
+		let shape = NetworkShape { inputs: 25, hidden: 8 };
+		let networks = [Network::new(shape), Network::new(shape), Network::new(shape)];
+		assert_eq!(networks[0].weights().len(), shape.weight_count());
+		let network_refs = networks.iter().collect::<Vec<&Network>>();
+		let inputs = (0..networks.len() * shape.inputs)
+			.map(|idx| (idx % 2) as f32)
+			.collect::<Vec<f32>>();
+
+		let batched = Network::forward_batch(&network_refs, &inputs);
+		assert_eq!(batched.len(), networks.len() * OUTPUT_NODES);
+
+		for (idx, row) in batched.chunks(OUTPUT_NODES).enumerate() {
+			let total: f32 = row.iter().sum();
+			assert!((total - 1.0).abs() < 1e-5);
+
+			// a batch must give the same result as evaluating each network on its own
+			let single = Network::forward_batch(&[&networks[idx]], &inputs[idx * shape.inputs..][..shape.inputs]);
+			for (a, b) in row.iter().zip(single.iter()) {
+				assert!((a - b).abs() < 1e-5);
+			}
+		}
+	}
+}
@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+use tch::{Device, nn, Tensor, kind};
+use tch::nn::VarStore;
+use crate::backend::network::{initial_weights, NetworkShape, OUTPUT_NODES};
+
+#[derive(Debug)]
+pub struct Network {
+	shape: NetworkShape,
+	var_store: VarStore
+}
+
+impl Network {
+
+	const VARIABLES: [&'static str; 4] = ["layer1.weight", "layer1.bias", "final.weight", "final.bias"];
+
+	pub fn new(shape: NetworkShape) -> Self {
+		// drawn from the simulation's generator rather than torch's, so seeded runs and resumed
+		// checkpoints give the same genomes as on the native backend
+		let mut network = Network::get_network(shape, None);
+		network.set_weights(&initial_weights(shape));
+		network
+	}
+
+	pub fn shape(&self) -> NetworkShape {
+		self.shape
+	}
+
+	fn get_network(shape: NetworkShape, vs: Option<&nn::VarStore>) -> Self {
+		let mut new_var_store = VarStore::new(Device::Cpu);
+		new_var_store.freeze();
+
+		// the layers are only built to register their variables; forward_batch applies them directly
+		let path = &new_var_store.root();
+		nn::linear(path / "layer1", shape.inputs as i64, shape.hidden as i64, Default::default());
+		nn::linear(path / "final", shape.hidden as i64, OUTPUT_NODES as i64, Default::default());
+
+		match vs {
+			None => {}
+			Some(new_weights) => {
+				new_var_store.copy(new_weights).unwrap();
+				new_var_store.freeze();
+			}
+		}
+
+		Network {
+			shape,
+			var_store: new_var_store
+		}
+	}
+
+	pub fn weights(&self) -> Vec<f32> {
+		let variables = self.var_store.variables();
+		Network::VARIABLES.iter()
+			.flat_map(|name| Vec::<f32>::from(&variables[*name]))
+			.collect()
+	}
+
+	pub fn set_weights(&mut self, weights: &[f32]) {
+		let variables = self.var_store.variables();
+		let mut offset = 0;
+		for name in Network::VARIABLES.iter() {
+			let mut var = variables[*name].shallow_clone();
+			let count = var.numel();
+			let new_var = Tensor::of_slice(&weights[offset..offset + count]).reshape(&var.size());
+			tch::no_grad(|| var.copy_(&new_var));
+			offset += count;
+		}
+	}
+
+	// Evaluates every network against its own row of `inputs` ([N, inputs], row-major) in a single
+	// pass by stacking the per-network weights and using batched matmuls. Returns the [N, OUTPUT_NODES]
+	// action probabilities, row-major.
+	pub fn forward_batch(networks: &[&Network], inputs: &[f32]) -> Vec<f32> {
+		if networks.is_empty() {
+			return Vec::new();
+		}
+
+		let variables = networks.iter()
+			.map(|network| network.var_store.variables())
+			.collect::<Vec<HashMap<String, Tensor>>>();
+		let stack = |name: &str| {
+			let tensors = variables.iter()
+				.map(|vars| vars[name].shallow_clone())
+				.collect::<Vec<Tensor>>();
+			Tensor::stack(&tensors, 0)
+		};
+
+		// linear weights are stored as [out, in], so transpose to [N, in, out] for bmm
+		let layer1_weight = stack("layer1.weight").transpose(1, 2);
+		let layer1_bias = stack("layer1.bias").unsqueeze(1);
+		let final_weight = stack("final.weight").transpose(1, 2);
+		let final_bias = stack("final.bias").unsqueeze(1);
+
+		let data = Tensor::of_slice(inputs).reshape(&[networks.len() as i64, 1, networks[0].shape.inputs as i64]);
+		let hidden = (data.bmm(&layer1_weight) + layer1_bias).tanh();
+		let output = (hidden.bmm(&final_weight) + final_bias)
+			.squeeze_dim(1)
+			.softmax(1, kind::Kind::Float);
+
+		Vec::<f32>::from(&output)
+	}
+}
+
+impl Clone for Network {
+	fn clone(&self) -> Self {
+		Network::get_network(self.shape, Some(&self.var_store))
+	}
+}
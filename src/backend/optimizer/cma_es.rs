@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+use rand::{Rng, RngCore};
+use crate::backend::random::rng;
+use rand_distr::StandardNormal;
+use crate::backend::error::Result;
+use crate::backend::evaluation::Evaluation;
+use crate::backend::gene::{Genome, GenomePool};
+use crate::backend::network::NetworkShape;
+use crate::backend::optimizer::Optimizer;
+
+// Separable CMA-ES (Ros & Hansen, 2008). The covariance is restricted to its diagonal, since a
+// full covariance over every network weight would need an O(n^3) eigendecomposition each generation.
+pub struct CmaEs {
+	lambda: usize,
+	mu: usize,
+	weights: Vec<f64>,
+	mu_eff: f64,
+	c_sigma: f64,
+	d_sigma: f64,
+	c_c: f64,
+	c_1: f64,
+	c_mu: f64,
+	expected_norm: f64,
+	shape: NetworkShape,
+
+	mean: Vec<f64>,
+	sigma: f64,
+	covariance: Vec<f64>,
+	path_sigma: Vec<f64>,
+	path_c: Vec<f64>,
+	generation: usize,
+	// the standard normal sample each asked genome was drawn from
+	samples: HashMap<u64, Vec<f64>>
+}
+
+impl CmaEs {
+	pub fn new(population_size: usize, sigma: f64, shape: NetworkShape) -> Self {
+		let mean = Genome::new(0, shape).weights()
+			.iter()
+			.map(|weight| *weight as f64)
+			.collect::<Vec<f64>>();
+		let n = mean.len() as f64;
+
+		let lambda = population_size.max(2);
+		let mu = lambda / 2;
+		let raw_weights = (0..mu)
+			.map(|i| (mu as f64 + 0.5).ln() - ((i + 1) as f64).ln())
+			.collect::<Vec<f64>>();
+		let weight_sum: f64 = raw_weights.iter().sum();
+		let weights = raw_weights.iter().map(|w| w / weight_sum).collect::<Vec<f64>>();
+		let mu_eff = 1f64 / weights.iter().map(|w| w * w).sum::<f64>();
+
+		let c_sigma = (mu_eff + 2f64) / (n + mu_eff + 5f64);
+		let d_sigma = 1f64 + 2f64 * (((mu_eff - 1f64) / (n + 1f64)).sqrt() - 1f64).max(0f64) + c_sigma;
+		let c_c = (4f64 + mu_eff / n) / (n + 4f64 + 2f64 * mu_eff / n);
+		// the separable variant can learn (n + 2) / 3 times faster than full CMA-ES
+		let separable_rate = (n + 2f64) / 3f64;
+		let c_1 = (separable_rate * 2f64 / ((n + 1.3f64).powi(2) + mu_eff)).min(1f64);
+		let c_mu = (separable_rate * 2f64 * (mu_eff - 2f64 + 1f64 / mu_eff) / ((n + 2f64).powi(2) + mu_eff))
+			.clamp(0f64, 1f64 - c_1);
+		let expected_norm = n.sqrt() * (1f64 - 1f64 / (4f64 * n) + 1f64 / (21f64 * n * n));
+
+		Self {
+			lambda,
+			mu,
+			weights,
+			mu_eff,
+			c_sigma,
+			d_sigma,
+			c_c,
+			c_1,
+			c_mu,
+			expected_norm,
+			shape,
+			sigma,
+			covariance: vec![1f64; mean.len()],
+			path_sigma: vec![0f64; mean.len()],
+			path_c: vec![0f64; mean.len()],
+			mean,
+			generation: 0,
+			samples: HashMap::new()
+		}
+	}
+}
+
+impl Optimizer for CmaEs {
+	fn ask(&mut self, genepool: &mut GenomePool) -> Result<Vec<u64>> {
+		let mut rng = rng();
+		self.samples.clear();
+
+		let ids = (0..self.lambda)
+			.map(|_| {
+				let z = (0..self.mean.len())
+					.map(|_| rng.sample::<f64, _>(StandardNormal))
+					.collect::<Vec<f64>>();
+				let weights = self.mean.iter()
+					.zip(self.covariance.iter())
+					.zip(z.iter())
+					.map(|((m, c), z)| (m + self.sigma * c.sqrt() * z) as f32)
+					.collect::<Vec<f32>>();
+
+				let mut genome = Genome::new(rng.next_u64(), self.shape);
+				genome.set_weights(&weights);
+				genome.ancestry.mutation = Some(format!("cma-es sample (sigma {:.4})", self.sigma));
+				let id = genome.id;
+				genepool.add_genome(genome);
+				self.samples.insert(id, z);
+				id
+			})
+			.collect();
+		Ok(ids)
+	}
+
+	fn tell(&mut self, _genepool: &GenomePool, evaluations: &HashMap<u64, Evaluation>) -> Result<()> {
+		let mut ranked = self.samples.keys().cloned().collect::<Vec<u64>>();
+		ranked.sort_by(|a, b| evaluations[b].fitness.total_cmp(&evaluations[a].fitness).then(a.cmp(b)));
+
+		let n = self.mean.len();
+		let mut z_w = vec![0f64; n];
+		let mut rank_mu = vec![0f64; n];
+		for (weight, id) in self.weights.iter().zip(ranked.iter().take(self.mu)) {
+			for (i, z) in self.samples[id].iter().enumerate() {
+				z_w[i] += weight * z;
+				// y = sqrt(C) z, so y^2 = C z^2
+				rank_mu[i] += weight * self.covariance[i] * z * z;
+			}
+		}
+		let y_w = z_w.iter()
+			.zip(self.covariance.iter())
+			.map(|(z, c)| c.sqrt() * z)
+			.collect::<Vec<f64>>();
+
+		for (m, y) in self.mean.iter_mut().zip(y_w.iter()) {
+			*m += self.sigma * y;
+		}
+
+		let sigma_rate = (self.c_sigma * (2f64 - self.c_sigma) * self.mu_eff).sqrt();
+		for (p, z) in self.path_sigma.iter_mut().zip(z_w.iter()) {
+			*p = (1f64 - self.c_sigma) * *p + sigma_rate * z;
+		}
+		let path_sigma_norm = self.path_sigma.iter().map(|p| p * p).sum::<f64>().sqrt();
+
+		self.generation += 1;
+		let stall_threshold = (1.4f64 + 2f64 / (n as f64 + 1f64)) * self.expected_norm;
+		let h_sigma = path_sigma_norm / (1f64 - (1f64 - self.c_sigma).powi(2 * self.generation as i32)).sqrt() < stall_threshold;
+		let h_sigma = if h_sigma { 1f64 } else { 0f64 };
+
+		let c_rate = (self.c_c * (2f64 - self.c_c) * self.mu_eff).sqrt();
+		for (p, y) in self.path_c.iter_mut().zip(y_w.iter()) {
+			*p = (1f64 - self.c_c) * *p + h_sigma * c_rate * y;
+		}
+
+		let stall_correction = (1f64 - h_sigma) * self.c_c * (2f64 - self.c_c);
+		for ((c, p), rank_mu) in self.covariance.iter_mut().zip(self.path_c.iter()).zip(rank_mu.iter()) {
+			*c = (1f64 - self.c_1 - self.c_mu) * *c
+				+ self.c_1 * (p * p + stall_correction * *c)
+				+ self.c_mu * rank_mu;
+		}
+
+		self.sigma *= ((self.c_sigma / self.d_sigma) * (path_sigma_norm / self.expected_norm - 1f64)).exp();
+		Ok(())
+	}
+
+	fn state(&self) -> serde_json::Value {
+		serde_json::json!({
+			"mean": self.mean,
+			"sigma": self.sigma,
+			"covariance": self.covariance,
+			"path_sigma": self.path_sigma,
+			"path_c": self.path_c,
+			"generation": self.generation
+		})
+	}
+
+	fn restore(&mut self, state: serde_json::Value) -> Result<()> {
+		self.mean = serde_json::from_value(state["mean"].clone())?;
+		self.sigma = serde_json::from_value(state["sigma"].clone())?;
+		self.covariance = serde_json::from_value(state["covariance"].clone())?;
+		self.path_sigma = serde_json::from_value(state["path_sigma"].clone())?;
+		self.path_c = serde_json::from_value(state["path_c"].clone())?;
+		self.generation = serde_json::from_value(state["generation"].clone())?;
+		Ok(())
+	}
+}
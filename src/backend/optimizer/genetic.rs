@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+use rand::RngCore;
+use crate::backend::random::rng;
+use crate::backend::error::Result;
+use crate::backend::evaluation::Evaluation;
+use crate::backend::gene::{Genome, GenomePool};
+use crate::backend::mutation::MutationOperator;
+use crate::backend::network::NetworkShape;
+use crate::backend::optimizer::Optimizer;
+
+// Truncation selection: the best `elite_count` genomes survive unchanged, mutants of the elites fill
+// the population and `random_count` fresh genomes are added every generation.
+pub struct GeneticAlgorithm {
+	population_size: usize,
+	elite_count: usize,
+	random_count: usize,
+	mutation: Vec<MutationOperator>,
+	shape: NetworkShape,
+	evaluations: HashMap<u64, Evaluation>
+}
+
+impl GeneticAlgorithm {
+	pub fn new(population_size: usize, elite_count: usize, random_count: usize, mutation: Vec<MutationOperator>, shape: NetworkShape) -> Self {
+		let elite_count = elite_count.min(population_size);
+		Self {
+			population_size,
+			elite_count,
+			random_count: random_count.min(population_size - elite_count),
+			mutation,
+			shape,
+			evaluations: HashMap::new()
+		}
+	}
+}
+
+impl Optimizer for GeneticAlgorithm {
+	fn ask(&mut self, genepool: &mut GenomePool) -> Result<Vec<u64>> {
+		let mut new_genes: Vec<Genome> = Vec::new();
+
+		if self.evaluations.is_empty() {
+			for _ in 0..self.population_size {
+				new_genes.push(Genome::new(rng().next_u64(), self.shape));
+			}
+			let new_ids = new_genes.iter().map(|gene| gene.id).collect();
+			for gene in new_genes {
+				genepool.add_genome(gene);
+			}
+			return Ok(new_ids);
+		}
+
+		let mut agent_score = self.evaluations
+			.iter()
+			.collect::<Vec<(&u64, &Evaluation)>>();
+
+		agent_score.sort_by(|(a_id, a), (b_id, b)| a.fitness.total_cmp(&b.fitness).then(a_id.cmp(b_id)));
+		let best = agent_score
+			.iter()
+			.rev()
+			.take(self.elite_count)
+			.map(|(id, evaluation)| (*id, *evaluation))
+			.collect::<Vec<(&u64, &Evaluation)>>();
+
+		let mut new_ids = best.iter()
+			.map(|(id, _)| **id)
+			.collect::<Vec<u64>>();
+
+		let mutant_count = self.population_size.saturating_sub(best.len() + self.random_count);
+		for (id, _) in best.iter().cycle().take(mutant_count) {
+			let mut mutated_gene = genepool.get_genome(**id)?.copy();
+			mutated_gene.mutate(&self.mutation);
+			new_genes.push(mutated_gene);
+		}
+
+		for _ in 0..self.random_count {
+			new_genes.push(Genome::new(rng().next_u64(), self.shape));
+		}
+
+		for gene in new_genes {
+			new_ids.push(gene.id);
+			genepool.add_genome(gene);
+		}
+
+		Ok(new_ids)
+	}
+
+	fn tell(&mut self, _genepool: &GenomePool, evaluations: &HashMap<u64, Evaluation>) -> Result<()> {
+		self.evaluations = evaluations.clone();
+		Ok(())
+	}
+
+	fn state(&self) -> serde_json::Value {
+		serde_json::json!({ "evaluations": self.evaluations })
+	}
+
+	fn restore(&mut self, state: serde_json::Value) -> Result<()> {
+		self.evaluations = serde_json::from_value(state["evaluations"].clone())?;
+		Ok(())
+	}
+
+	// Migrants displace the weakest genomes of the last generation, so they compete for the elite slots.
+	fn immigrate(&mut self, _genepool: &GenomePool, migrants: &HashMap<u64, Evaluation>) -> Result<()> {
+		let mut ranked = self.evaluations.iter()
+			.map(|(id, evaluation)| (*id, evaluation.fitness))
+			.collect::<Vec<(u64, f64)>>();
+		ranked.sort_by(|(a_id, a), (b_id, b)| a.total_cmp(b).then(a_id.cmp(b_id)));
+
+		for (id, _) in ranked.iter().take(migrants.len()) {
+			self.evaluations.remove(id);
+		}
+		self.evaluations.extend(migrants.iter().map(|(id, evaluation)| (*id, evaluation.clone())));
+		Ok(())
+	}
+}
@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use rand::RngCore;
+use rand::seq::IteratorRandom;
+use crate::backend::archive::GenomeRecord;
+use crate::backend::behaviour::Behaviour;
+use crate::backend::error::Result;
+use crate::backend::evaluation::Evaluation;
+use crate::backend::gene::{Genome, GenomePool};
+use crate::backend::mutation::MutationOperator;
+use crate::backend::network::NetworkShape;
+use crate::backend::random::rng;
+use crate::backend::optimizer::Optimizer;
+
+#[derive(Debug, Clone)]
+pub struct Elite {
+	pub genome: Genome,
+	pub fitness: f64,
+	pub descriptor: Vec<f64>
+}
+
+// MAP-Elites (Mouret & Clune, 2015): a grid over the chosen behaviour descriptor dimensions that
+// keeps the fittest genome seen in every cell. Parents are drawn uniformly from the filled cells.
+pub struct MapElites {
+	population_size: usize,
+	// indices into Behaviour::DIMENSION_NAMES
+	dimensions: Vec<usize>,
+	bins: usize,
+	mutation: Vec<MutationOperator>,
+	shape: NetworkShape,
+	archive: HashMap<Vec<usize>, Elite>
+}
+
+impl MapElites {
+	pub fn new(population_size: usize, dimensions: Vec<usize>, bins: usize, mutation: Vec<MutationOperator>, shape: NetworkShape) -> Self {
+		Self {
+			population_size,
+			dimensions,
+			bins: bins.max(1),
+			mutation,
+			shape,
+			archive: HashMap::new()
+		}
+	}
+
+	#[cfg(test)]
+	pub fn archive(&self) -> &HashMap<Vec<usize>, Elite> {
+		&self.archive
+	}
+
+	pub fn cell(&self, descriptor: &[f64]) -> Vec<usize> {
+		self.dimensions.iter()
+			.map(|dimension| {
+				let value = descriptor.get(*dimension).cloned().unwrap_or(0f64).clamp(0f64, 1f64);
+				((value * self.bins as f64) as usize).min(self.bins - 1)
+			})
+			.collect()
+	}
+
+	// One row per filled cell: the cell coordinates, the elite's full descriptor, its fitness and id.
+	pub fn export_csv(&self, path: &Path) -> Result<()> {
+		let mut writer = BufWriter::new(File::create(path)?);
+
+		let mut header = self.dimensions.iter()
+			.map(|dimension| format!("cell_{}", Behaviour::DIMENSION_NAMES[*dimension]))
+			.collect::<Vec<String>>();
+		header.extend(Behaviour::DIMENSION_NAMES.iter().map(|name| name.to_string()));
+		header.push("fitness".to_string());
+		header.push("genome".to_string());
+		writeln!(writer, "{}", header.join(","))?;
+
+		let mut cells = self.archive.keys().collect::<Vec<&Vec<usize>>>();
+		cells.sort();
+		for cell in cells {
+			let elite = &self.archive[cell];
+			let mut row = cell.iter().map(|idx| idx.to_string()).collect::<Vec<String>>();
+			row.extend(elite.descriptor.iter().map(|value| format!("{:.4}", value)));
+			row.push(format!("{:.4}", elite.fitness));
+			row.push(elite.genome.id.to_string());
+			writeln!(writer, "{}", row.join(","))?;
+		}
+		Ok(writer.flush()?)
+	}
+}
+
+impl Optimizer for MapElites {
+	fn ask(&mut self, genepool: &mut GenomePool) -> Result<Vec<u64>> {
+		let mut rng = rng();
+		let mut cells = self.archive.keys().collect::<Vec<&Vec<usize>>>();
+		cells.sort();
+
+		let ids = (0..self.population_size)
+			.map(|_| {
+				let genome = match cells.iter().choose(&mut rng).map(|cell| &self.archive[*cell]) {
+					None => Genome::new(rng.next_u64(), self.shape),
+					Some(parent) => {
+						let mut genome = parent.genome.copy();
+						genome.mutate(&self.mutation);
+						genome
+					}
+				};
+				let id = genome.id;
+				genepool.add_genome(genome);
+				id
+			})
+			.collect();
+		Ok(ids)
+	}
+
+	fn tell(&mut self, genepool: &GenomePool, evaluations: &HashMap<u64, Evaluation>) -> Result<()> {
+		for (id, evaluation) in evaluations {
+			let descriptor = evaluation.descriptor();
+			let cell = self.cell(&descriptor);
+
+			let improves = self.archive.get(&cell)
+				.map(|elite| evaluation.fitness > elite.fitness)
+				.unwrap_or(true);
+			if improves {
+				self.archive.insert(cell, Elite {
+					genome: genepool.get_genome(*id)?.clone(),
+					fitness: evaluation.fitness,
+					descriptor
+				});
+			}
+		}
+		Ok(())
+	}
+
+	fn immigrate(&mut self, genepool: &GenomePool, migrants: &HashMap<u64, Evaluation>) -> Result<()> {
+		self.tell(genepool, migrants)
+	}
+
+	fn export(&self, directory: &Path) -> Result<()> {
+		self.export_csv(&directory.join("map_elites.csv"))
+	}
+
+	fn state(&self) -> serde_json::Value {
+		let elites = self.archive.values()
+			.map(|elite| serde_json::json!({
+				"genome": GenomeRecord::from_genome(&elite.genome),
+				"fitness": elite.fitness,
+				"descriptor": elite.descriptor
+			}))
+			.collect::<Vec<serde_json::Value>>();
+		serde_json::json!({ "elites": elites })
+	}
+
+	fn restore(&mut self, state: serde_json::Value) -> Result<()> {
+		self.archive.clear();
+		for elite in state["elites"].as_array().cloned().unwrap_or_default() {
+			let record: GenomeRecord = serde_json::from_value(elite["genome"].clone())?;
+			let descriptor: Vec<f64> = serde_json::from_value(elite["descriptor"].clone())?;
+			self.archive.insert(self.cell(&descriptor), Elite {
+				genome: record.to_genome(self.shape)?,
+				fitness: serde_json::from_value(elite["fitness"].clone())?,
+				descriptor
+			});
+		}
+		Ok(())
+	}
+}
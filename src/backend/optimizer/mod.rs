@@ -0,0 +1,91 @@
+use std::collections::HashMap;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use crate::backend::error::Result;
+use crate::backend::evaluation::Evaluation;
+use crate::backend::gene::GenomePool;
+use crate::backend::mutation::MutationOperator;
+use crate::backend::network::NetworkShape;
+use crate::backend::optimizer::cma_es::CmaEs;
+use crate::backend::optimizer::genetic::GeneticAlgorithm;
+use crate::backend::optimizer::map_elites::MapElites;
+use crate::backend::optimizer::natural_es::NaturalEs;
+
+mod genetic;
+mod cma_es;
+mod natural_es;
+mod map_elites;
+mod optimizer_tests;
+
+/// Ask/tell interface the Orchestrator drives once per generation: `ask` adds the genomes to play
+/// to the pool and returns their ids, `tell` hands back every one of those genomes' evaluations.
+pub trait Optimizer {
+	/// Adds the next genomes to evaluate to `genepool` and returns their ids.
+	fn ask(&mut self, genepool: &mut GenomePool) -> Result<Vec<u64>>;
+	/// Takes the evaluations of every genome the last `ask` returned.
+	fn tell(&mut self, genepool: &GenomePool, evaluations: &HashMap<u64, Evaluation>) -> Result<()>;
+
+	/// Offers genomes evaluated by another population. They are already in `genepool`; optimizers
+	/// that sample around a single search distribution ignore them, see OptimizerKind::accepts_migrants.
+	fn immigrate(&mut self, _genepool: &GenomePool, _migrants: &HashMap<u64, Evaluation>) -> Result<()> {
+		Ok(())
+	}
+
+	/// Writes any optimizer state worth keeping after a run into `directory`.
+	fn export(&self, _directory: &Path) -> Result<()> {
+		Ok(())
+	}
+
+	/// Everything `restore` needs to carry on from the last `tell`, for checkpoints.
+	fn state(&self) -> serde_json::Value;
+	/// Carries on from a `state` saved by the same kind of optimizer.
+	fn restore(&mut self, state: serde_json::Value) -> Result<()>;
+}
+
+/// The optimizers an experiment can choose between, with their settings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum OptimizerKind {
+	/// truncation selection keeping `elites` unchanged and adding `randoms` fresh genomes each generation
+	Genetic { elites: usize, randoms: usize },
+	/// separable (diagonal covariance) CMA-ES over the flattened weights
+	CmaEs { sigma: f64 },
+	/// OpenAI-style natural evolution strategy with antithetic sampling and rank-normalised fitness
+	NaturalEs { sigma: f64, learning_rate: f64 },
+	/// quality-diversity grid over the given Behaviour descriptor dimensions, `bins` cells per dimension
+	MapElites { dimensions: Vec<usize>, bins: usize }
+}
+
+impl OptimizerKind {
+	/// Whether the optimizer makes use of `immigrate`. The distribution-based ones (CMA-ES, NES)
+	/// would drop migrants, so islands can't use them.
+	pub fn accepts_migrants(&self) -> bool {
+		matches!(self, OptimizerKind::Genetic { .. } | OptimizerKind::MapElites { .. })
+	}
+
+	/// Builds the optimizer for populations of `population_size` genomes with networks of `shape`.
+	pub fn build(&self, population_size: usize, mutation: &[MutationOperator], shape: NetworkShape) -> Box<dyn Optimizer> {
+		match self {
+			OptimizerKind::Genetic { elites, randoms } => {
+				Box::new(GeneticAlgorithm::new(population_size, *elites, *randoms, mutation.to_vec(), shape))
+			}
+			OptimizerKind::CmaEs { sigma } => Box::new(CmaEs::new(population_size, *sigma, shape)),
+			OptimizerKind::NaturalEs { sigma, learning_rate } => Box::new(NaturalEs::new(population_size, *sigma, *learning_rate, shape)),
+			OptimizerKind::MapElites { dimensions, bins } => {
+				Box::new(MapElites::new(population_size, dimensions.clone(), *bins, mutation.to_vec(), shape))
+			}
+		}
+	}
+}
+
+// Maps each id to its fitness rank, rescaled into [-0.5, 0.5] (best is 0.5).
+pub(crate) fn centered_ranks(ids: &[u64], evaluations: &HashMap<u64, Evaluation>) -> HashMap<u64, f64> {
+	let mut sorted = ids.to_vec();
+	sorted.sort_by(|a, b| evaluations[a].fitness.total_cmp(&evaluations[b].fitness).then(a.cmp(b)));
+
+	let denominator = (sorted.len().max(2) - 1) as f64;
+	sorted.iter()
+		.enumerate()
+		.map(|(rank, id)| (*id, rank as f64 / denominator - 0.5))
+		.collect()
+}
@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+use rand::{Rng, RngCore};
+use crate::backend::random::rng;
+use rand_distr::StandardNormal;
+use crate::backend::error::Result;
+use crate::backend::evaluation::Evaluation;
+use crate::backend::gene::{Genome, GenomePool};
+use crate::backend::network::NetworkShape;
+use crate::backend::optimizer::{centered_ranks, Optimizer};
+
+// OpenAI-ES (Salimans et al., 2017): perturbs a single parameter vector with antithetic gaussian
+// noise pairs and follows the rank-normalised fitness gradient estimate.
+pub struct NaturalEs {
+	// an odd population leaves the last pair without its mirrored sample
+	population_size: usize,
+	sigma: f64,
+	learning_rate: f64,
+	shape: NetworkShape,
+	mean: Vec<f64>,
+	// the noise each asked genome was built from, already signed for its side of the pair
+	samples: HashMap<u64, Vec<f64>>
+}
+
+impl NaturalEs {
+	pub fn new(population_size: usize, sigma: f64, learning_rate: f64, shape: NetworkShape) -> Self {
+		let mean = Genome::new(0, shape).weights()
+			.iter()
+			.map(|weight| *weight as f64)
+			.collect();
+
+		Self {
+			population_size: population_size.max(1),
+			sigma,
+			learning_rate,
+			shape,
+			mean,
+			samples: HashMap::new()
+		}
+	}
+
+	fn add_genome(&mut self, genepool: &mut GenomePool, noise: Vec<f64>) -> u64 {
+		let weights = self.mean.iter()
+			.zip(noise.iter())
+			.map(|(m, e)| (m + self.sigma * e) as f32)
+			.collect::<Vec<f32>>();
+
+		let mut genome = Genome::new(rng().next_u64(), self.shape);
+		genome.set_weights(&weights);
+		genome.ancestry.mutation = Some(format!("natural-es sample (sigma {:.4})", self.sigma));
+		let id = genome.id;
+		genepool.add_genome(genome);
+		self.samples.insert(id, noise);
+		id
+	}
+}
+
+impl Optimizer for NaturalEs {
+	fn ask(&mut self, genepool: &mut GenomePool) -> Result<Vec<u64>> {
+		let mut rng = rng();
+		self.samples.clear();
+
+		let mut ids = Vec::with_capacity(self.population_size);
+		while ids.len() < self.population_size {
+			let noise = (0..self.mean.len())
+				.map(|_| rng.sample::<f64, _>(StandardNormal))
+				.collect::<Vec<f64>>();
+			let mirrored = noise.iter().map(|e| -e).collect::<Vec<f64>>();
+
+			ids.push(self.add_genome(genepool, noise));
+			if ids.len() < self.population_size {
+				ids.push(self.add_genome(genepool, mirrored));
+			}
+		}
+		Ok(ids)
+	}
+
+	fn tell(&mut self, _genepool: &GenomePool, evaluations: &HashMap<u64, Evaluation>) -> Result<()> {
+		let mut ids = self.samples.keys().cloned().collect::<Vec<u64>>();
+		ids.sort();
+		let ranks = centered_ranks(&ids, evaluations);
+
+		let mut gradient = vec![0f64; self.mean.len()];
+		for id in &ids {
+			let rank = ranks[id];
+			for (g, e) in gradient.iter_mut().zip(self.samples[id].iter()) {
+				*g += rank * e;
+			}
+		}
+
+		let scale = self.learning_rate / (self.samples.len() as f64 * self.sigma);
+		for (m, g) in self.mean.iter_mut().zip(gradient.iter()) {
+			*m += scale * g;
+		}
+		Ok(())
+	}
+
+	fn state(&self) -> serde_json::Value {
+		serde_json::json!({ "mean": self.mean })
+	}
+
+	fn restore(&mut self, state: serde_json::Value) -> Result<()> {
+		self.mean = serde_json::from_value(state["mean"].clone())?;
+		Ok(())
+	}
+}
@@ -0,0 +1,103 @@
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod optimizer_tests {
+	use std::collections::HashMap;
+	use crate::backend::evaluation::{Aggregation, Evaluation};
+	use crate::backend::gene::GenomePool;
+	use crate::backend::mutation::MutationOperator;
+	use crate::backend::behaviour::Behaviour;
+	use crate::backend::map::Action;
+	use crate::backend::network::NetworkShape;
+	use crate::backend::optimizer::map_elites::MapElites;
+	use crate::backend::optimizer::{Optimizer, OptimizerKind};
+	use crate::backend::random;
+
+	// Fitness that rewards pulling every weight towards 0.5, standing in for a match.
+	fn evaluate(genepool: &GenomePool, ids: &[u64]) -> HashMap<u64, Evaluation> {
+		ids.iter()
+			.map(|id| {
+				let distance: f64 = genepool.get_genome(*id).unwrap().weights()
+					.iter()
+					.map(|weight| (*weight as f64 - 0.5).powi(2))
+					.sum();
+				let mut evaluation = Evaluation::new(Vec::new(), Vec::new(), Aggregation::Mean);
+				evaluation.fitness = -distance;
+				(*id, evaluation)
+			})
+			.collect()
+	}
+
+	fn best_fitness(kind: OptimizerKind, generations: usize) -> (f64, f64) {
+		let mut optimizer = kind.build(10, &[MutationOperator::Gaussian { sigma: 0.02, probability: 1.0 }], NetworkShape::DEFAULT);
+		let mut genepool = GenomePool::new();
+
+		let mut first = None;
+		let mut last = f64::NEG_INFINITY;
+		for _ in 0..generations {
+			let ids = optimizer.ask(&mut genepool).unwrap();
+			assert_eq!(ids.len(), 10);
+
+			let evaluations = evaluate(&genepool, &ids);
+			last = evaluations.values().map(|evaluation| evaluation.fitness).fold(f64::NEG_INFINITY, f64::max);
+			first.get_or_insert(last);
+			optimizer.tell(&genepool, &evaluations).unwrap();
+		}
+		(first.unwrap(), last)
+	}
+
+	#[test]
+	fn map_elites_keeps_best_per_cell() {
+		random::seed(1);
+		let mut optimizer = MapElites::new(10, vec![0, 1], 4, MutationOperator::defaults(), NetworkShape::DEFAULT);
+		let mut genepool = GenomePool::new();
+
+		for _ in 0..5 {
+			let ids = optimizer.ask(&mut genepool).unwrap();
+			let mut evaluations = evaluate(&genepool, &ids);
+			for (id, evaluation) in evaluations.iter_mut() {
+				// spread genomes over the grid by their first two weights
+				let weights = genepool.get_genome(*id).unwrap().weights();
+				evaluation.behaviours = vec![Behaviour {
+					final_position: [weights[0] as f64 * 3.0 + 0.5, weights[1] as f64 * 3.0 + 0.5],
+					coverage: 0f64,
+					action_histogram: [0f64; Action::COUNT]
+				}];
+			}
+			optimizer.tell(&genepool, &evaluations).unwrap();
+
+			for evaluation in evaluations.values() {
+				let elite = &optimizer.archive()[&optimizer.cell(&evaluation.descriptor())];
+				assert!(elite.fitness >= evaluation.fitness);
+			}
+		}
+		assert!(optimizer.archive().len() > 1);
+	}
+
+	#[test]
+	fn genetic_improves() {
+		random::seed(2);
+		let (first, last) = best_fitness(OptimizerKind::Genetic { elites: 4, randoms: 2 }, 30);
+		assert!(last > first);
+	}
+
+	#[test]
+	fn cma_es_improves() {
+		random::seed(3);
+		let (first, last) = best_fitness(OptimizerKind::CmaEs { sigma: 0.05 }, 30);
+		assert!(last > first);
+	}
+
+	#[test]
+	fn natural_es_improves() {
+		random::seed(4);
+		let (first, last) = best_fitness(OptimizerKind::NaturalEs { sigma: 0.05, learning_rate: 0.005 }, 50);
+		assert!(last > first);
+
+		// an odd population is filled rather than rounded down to whole pairs
+		let mut optimizer = OptimizerKind::NaturalEs { sigma: 0.05, learning_rate: 0.005 }.build(7, &[], NetworkShape::DEFAULT);
+		let mut genepool = GenomePool::new();
+		let ids = optimizer.ask(&mut genepool).unwrap();
+		assert_eq!(ids.len(), 7);
+		optimizer.tell(&genepool, &evaluate(&genepool, &ids)).unwrap();
+	}
+}
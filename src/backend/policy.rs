@@ -0,0 +1,71 @@
+use std::fmt;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use rand::distributions::{Distribution, WeightedIndex};
+use crate::backend::agent::AgentSense;
+use crate::backend::map::Action;
+
+/// Decides what an agent does with what it senses. Genomes are the evolved policy; Baseline
+/// provides fixed behaviours to score them against.
+pub trait Policy: fmt::Debug + Send {
+	/// `mode` says how a network's output becomes an action; policies without one may ignore it.
+	fn act(&self, sense: &AgentSense, mode: PolicyMode) -> Action;
+}
+
+/// How an action index is picked from a network's softmax output.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum PolicyMode {
+	/// sample from the softmax distribution
+	Stochastic,
+	/// always take the most likely action
+	Greedy,
+	/// take a uniformly random action with probability `epsilon`, otherwise the most likely one
+	EpsilonGreedy { epsilon: f64 },
+	/// sample from the softmax sharpened (< 1) or flattened (> 1) by `temperature`
+	Temperature { temperature: f64 }
+}
+
+impl PolicyMode {
+	/// Picks an action index from `probabilities`, a softmax output.
+	pub fn select<R: Rng>(&self, probabilities: &[f32], rng: &mut R) -> i64 {
+		match self {
+			PolicyMode::Stochastic => sample(probabilities, rng),
+			PolicyMode::Greedy => argmax(probabilities),
+			PolicyMode::EpsilonGreedy { epsilon } => {
+				if rng.gen_bool(epsilon.clamp(0f64, 1f64)) {
+					rng.gen_range(0..probabilities.len()) as i64
+				} else {
+					argmax(probabilities)
+				}
+			}
+			PolicyMode::Temperature { temperature } => {
+				if *temperature <= 0f64 {
+					return argmax(probabilities);
+				}
+				// softmax(logits / t) is the softmax output raised to 1 / t and renormalised
+				let exponent = 1f64 / temperature;
+				let scaled = probabilities.iter()
+					.map(|p| (*p as f64).powf(exponent))
+					.collect::<Vec<f64>>();
+				if scaled.iter().sum::<f64>() > 0f64 {
+					WeightedIndex::new(&scaled).unwrap().sample(rng) as i64
+				} else {
+					argmax(probabilities)
+				}
+			}
+		}
+	}
+}
+
+fn sample<R: Rng>(probabilities: &[f32], rng: &mut R) -> i64 {
+	WeightedIndex::new(probabilities).unwrap().sample(rng) as i64
+}
+
+fn argmax(probabilities: &[f32]) -> i64 {
+	probabilities.iter()
+		.enumerate()
+		.max_by(|(_, a), (_, b)| a.total_cmp(b))
+		.map(|(idx, _)| idx as i64)
+		.unwrap_or(0)
+}
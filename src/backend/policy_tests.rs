@@ -0,0 +1,44 @@
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod policy_tests {
+	use rand::rngs::StdRng;
+	use rand::SeedableRng;
+	use crate::backend::policy::PolicyMode;
+
+	const PROBABILITIES: [f32; 5] = [0.1, 0.2, 0.6, 0.05, 0.05];
+	const DRAWS: usize = 2000;
+
+	fn counts(mode: PolicyMode) -> [usize; 5] {
+		let mut rng = StdRng::seed_from_u64(3);
+		let mut counts = [0; 5];
+		for _ in 0..DRAWS {
+			counts[mode.select(&PROBABILITIES, &mut rng) as usize] += 1;
+		}
+		counts
+	}
+
+	#[test]
+	fn greedy_takes_argmax() {
+		assert_eq!(counts(PolicyMode::Greedy), [0, 0, DRAWS, 0, 0]);
+	}
+
+	#[test]
+	fn epsilon_limits() {
+		assert_eq!(counts(PolicyMode::EpsilonGreedy { epsilon: 0.0 }), counts(PolicyMode::Greedy));
+
+		// fully random: every action turns up about as often as the others
+		let uniform = counts(PolicyMode::EpsilonGreedy { epsilon: 1.0 });
+		for count in uniform {
+			assert!((count as f64 - DRAWS as f64 / 5.0).abs() < DRAWS as f64 * 0.05);
+		}
+	}
+
+	#[test]
+	fn cold_temperature_is_greedy() {
+		assert_eq!(counts(PolicyMode::Temperature { temperature: 0.01 }), counts(PolicyMode::Greedy));
+		assert_eq!(counts(PolicyMode::Temperature { temperature: 0.0 }), counts(PolicyMode::Greedy));
+
+		// at 1 it samples the distribution as given, so other actions still turn up
+		assert!(counts(PolicyMode::Temperature { temperature: 1.0 })[2] < DRAWS);
+	}
+}
@@ -0,0 +1,65 @@
+use std::cell::RefCell;
+use std::sync::Mutex;
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+
+// Seed new threads' generators are derived from; None means seed from entropy.
+static SEED: Mutex<Option<u64>> = Mutex::new(None);
+
+thread_local! {
+	static RNG: RefCell<StdRng> = RefCell::new(fresh_rng());
+}
+
+fn fresh_rng() -> StdRng {
+	match *SEED.lock().unwrap() {
+		Some(seed) => StdRng::seed_from_u64(seed),
+		None => StdRng::from_entropy()
+	}
+}
+
+// Seeds every random draw the simulation makes on this thread, and the generators of threads that
+// have not drawn anything yet. Worker threads are reseeded per match (see Orchestrator::play_matches)
+// so a seeded run is reproducible whatever the thread count.
+pub fn seed(seed: u64) {
+	*SEED.lock().unwrap() = Some(seed);
+	reseed(seed);
+}
+
+// Reseeds only the current thread's generator.
+pub fn reseed(seed: u64) {
+	RNG.with(|rng| *rng.borrow_mut() = StdRng::seed_from_u64(seed));
+}
+
+// Reseeds the current thread's generator with a seed drawn from it and returns the seed, so
+// whatever is drawn next depends on that seed alone.
+pub fn advance() -> u64 {
+	let seed = rng().next_u64();
+	reseed(seed);
+	seed
+}
+
+// Handle to the current thread's generator; use in place of rand::thread_rng.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SimRng;
+
+pub fn rng() -> SimRng {
+	SimRng
+}
+
+impl RngCore for SimRng {
+	fn next_u32(&mut self) -> u32 {
+		RNG.with(|rng| rng.borrow_mut().next_u32())
+	}
+
+	fn next_u64(&mut self) -> u64 {
+		RNG.with(|rng| rng.borrow_mut().next_u64())
+	}
+
+	fn fill_bytes(&mut self, dest: &mut [u8]) {
+		RNG.with(|rng| rng.borrow_mut().fill_bytes(dest))
+	}
+
+	fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+		RNG.with(|rng| rng.borrow_mut().try_fill_bytes(dest))
+	}
+}
@@ -0,0 +1,223 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use crate::backend::agent::Agent;
+use crate::backend::archive::GenomeRecord;
+use crate::backend::engine::{Engine, EngineConfig};
+use crate::backend::entity::{Entity, EntityType};
+use crate::backend::error::{Error, Result};
+use crate::backend::map::Action;
+use crate::backend::network::NetworkShape;
+use crate::backend::Position;
+
+/// Everything needed to rebuild a match's starting state.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayHeader {
+	/// the engine the match was played in
+	pub config: EngineConfig,
+	/// see Engine::round
+	pub round: usize,
+	/// one per agent, in the same order; none for scripted and idle agents
+	pub genomes: Vec<Option<GenomeRecord>>,
+	/// sorted by id
+	pub agents: Vec<AgentStart>,
+	/// tiles holding food at the start
+	pub food: Vec<[i32; 2]>
+}
+
+/// Where an agent started the match.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct AgentStart {
+	/// see Agent::id
+	pub id: u64,
+	/// the agent's tile
+	pub position: [i32; 2]
+}
+
+/// Where an agent was after one step and what it did.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct AgentFrame {
+	/// see Agent::id
+	pub id: u64,
+	/// where the agent ended up after the step
+	pub position: [i32; 2],
+	/// Action::index of what it chose, or None if it sat the step out (see Engine::step_with)
+	pub action: Option<usize>
+}
+
+/// Everything that changed in one step of a match.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayStep {
+	/// Engine::round_idx before the step was played
+	pub round_idx: usize,
+	/// every agent after the step, sorted by id
+	pub agents: Vec<AgentFrame>,
+	/// food tiles eaten this step, and by whom
+	pub food_eaten: Vec<(u64, [i32; 2])>,
+	/// tiles food was placed on after agents moved, see EngineConfig::food_rate
+	pub food_spawned: Vec<[i32; 2]>
+}
+
+/// A per-step trace of one match, written by an Engine with `record_replay` set.
+/// Stored as JSON Lines: the header first, then one line per step.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Replay {
+	/// the match's starting state
+	pub header: ReplayHeader,
+	/// in the order they were played
+	pub steps: Vec<ReplayStep>
+}
+
+fn to_array(position: Position) -> [i32; 2] {
+	[position.x, position.y]
+}
+
+fn to_position(position: [i32; 2]) -> Position {
+	Position::new(position[0], position[1])
+}
+
+impl Replay {
+	/// Starts a trace from the engine's current (freshly initialised) state.
+	pub fn begin(engine: &Engine) -> Self {
+		let mut agents = engine.agents.values().collect::<Vec<&Agent>>();
+		agents.sort_by_key(|agent| agent.id);
+
+		let mut food = engine.entities.keys().cloned().map(to_array).collect::<Vec<[i32; 2]>>();
+		food.sort();
+
+		Self {
+			header: ReplayHeader {
+				config: engine.config.clone(),
+				round: engine.round,
+				genomes: agents.iter().map(|agent| agent.genome.as_ref().map(GenomeRecord::from_genome)).collect(),
+				agents: agents.iter()
+					.map(|agent| AgentStart { id: agent.id, position: to_array(agent.position) })
+					.collect(),
+				food
+			},
+			steps: Vec::new()
+		}
+	}
+
+	/// Appends the step the engine just played: the agents' `actions` and the food they ate and that spawned.
+	pub fn record_step(&mut self, engine: &Engine, actions: &HashMap<u64, Action>, food_eaten: &[(u64, Position)], food_spawned: &[Position]) {
+		let mut agents = engine.agents.values()
+			.map(|agent| AgentFrame {
+				id: agent.id,
+				position: to_array(agent.position),
+				action: actions.get(&agent.id).map(Action::index)
+			})
+			.collect::<Vec<AgentFrame>>();
+		agents.sort_by_key(|frame| frame.id);
+
+		self.steps.push(ReplayStep {
+			round_idx: engine.round_idx,
+			agents,
+			food_eaten: food_eaten.iter().map(|(id, position)| (*id, to_array(*position))).collect(),
+			food_spawned: food_spawned.iter().cloned().map(to_array).collect()
+		});
+	}
+
+	/// Number of steps recorded.
+	pub fn len(&self) -> usize {
+		self.steps.len()
+	}
+
+	/// Whether no steps have been recorded.
+	pub fn is_empty(&self) -> bool {
+		self.steps.is_empty()
+	}
+
+	/// The engine as it was after `step` steps (0 being the initial state). Agents carry the
+	/// recorded genomes, so the returned engine can be stepped onwards; scripted agents come back
+	/// idle, their policies not being recorded.
+	pub fn engine_at(&self, step: usize) -> Result<Engine> {
+		let step = step.min(self.steps.len());
+		let mut engine = Engine::new(self.header.config.clone())?;
+		engine.round = self.header.round;
+
+		for food in &self.header.food {
+			let position = to_position(*food);
+			engine.entities.insert(position, Entity::new(position, EntityType::Food));
+		}
+		for (start, genome) in self.header.agents.iter().zip(&self.header.genomes) {
+			let position = to_position(start.position);
+			let agent = match genome {
+				Some(genome) => {
+					let shape = NetworkShape { inputs: self.header.config.vision_tiles(), hidden: genome.shape.hidden };
+					Agent::new(start.id, position, genome.to_genome(shape)?)
+				}
+				None => Agent::idle(start.id, position)
+			};
+			engine.agents.insert(start.id, agent);
+		}
+
+		for _ in 0..step {
+			self.apply_step(&mut engine)?;
+		}
+		Ok(engine)
+	}
+
+	/// Advances an engine rebuilt by `engine_at` by one recorded step, if any are left. The match
+	/// concludes after the config's round_max rounds, as it did when recorded, so a truncated
+	/// recording plays back as an unfinished match.
+	pub fn apply_step(&self, engine: &mut Engine) -> Result<()> {
+		let Some(recorded) = self.steps.get(engine.round_idx) else {
+			return Ok(());
+		};
+
+		for frame in &recorded.agents {
+			let agent = engine.agents.get_mut(&frame.id).ok_or(Error::UnknownAgent(frame.id))?;
+			// agents that didn't act stayed where they were and took no step
+			if let Some(action) = frame.action {
+				agent.stats.steps_taken += 1;
+				agent.record_move(&Action::from_index(action as i64), to_position(frame.position));
+			}
+		}
+		for (id, food) in &recorded.food_eaten {
+			engine.entities.remove(&to_position(*food));
+			engine.agents.get_mut(id).ok_or(Error::UnknownAgent(*id))?.increment_food();
+		}
+		for food in &recorded.food_spawned {
+			let position = to_position(*food);
+			engine.entities.insert(position, Entity::new(position, EntityType::Food));
+		}
+
+		engine.round_idx += 1;
+		engine.game_concluded = engine.round_idx >= self.header.config.round_max;
+		Ok(())
+	}
+
+	/// Writes the replay to `path` as JSON Lines.
+	pub fn save(&self, path: &Path) -> Result<()> {
+		let mut writer = BufWriter::new(File::create(path)?);
+		serde_json::to_writer(&mut writer, &self.header)?;
+		writeln!(writer)?;
+		for step in &self.steps {
+			serde_json::to_writer(&mut writer, step)?;
+			writeln!(writer)?;
+		}
+		Ok(writer.flush()?)
+	}
+
+	/// Reads a replay written by `save`.
+	pub fn load(path: &Path) -> Result<Self> {
+		let mut lines = BufReader::new(File::open(path)?).lines();
+		let header = match lines.next() {
+			Some(line) => serde_json::from_str(&line?)?,
+			None => return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "replay has no header").into())
+		};
+
+		let mut steps = Vec::new();
+		for line in lines {
+			steps.push(serde_json::from_str(&line?)?);
+		}
+
+		Ok(Self {
+			header,
+			steps
+		})
+	}
+}
@@ -0,0 +1,87 @@
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod replay_tests {
+	use std::collections::HashMap;
+	use std::fs;
+	use crate::backend::agent::Agent;
+	use crate::backend::engine::{Engine, EngineConfig};
+	use crate::backend::gene::Genome;
+	use crate::backend::map::{Action, Direction};
+	use crate::backend::replay::Replay;
+	use crate::backend::Position;
+
+	#[test]
+	fn reconstructs_final_state() {
+		let mut engine = Engine::new(EngineConfig {
+			food_spread: 2.5f64,
+			size: [20f64, 20f64],
+			round_max: 40,
+			agent_count: 3,
+			food_rate: 0.5f64,
+			..EngineConfig::default()
+		}).unwrap();
+		engine.record_replay = true;
+
+		let agents = (0..3u64)
+			.map(|id| (id, Agent::new(id, Position::new(id as i32 * 5, 10), Genome::blank(id))))
+			.collect::<HashMap<u64, Agent>>();
+		let result = engine.play_match(agents, 7).unwrap();
+		let replay = engine.replay.take().unwrap();
+		assert_eq!(replay.len(), result.duration);
+
+		let path = std::env::temp_dir().join(format!("cadmus_replay_{}.jsonl", std::process::id()));
+		replay.save(&path).unwrap();
+		let loaded = Replay::load(&path).unwrap();
+		fs::remove_file(&path).unwrap();
+
+		let start = loaded.engine_at(0).unwrap();
+		assert_eq!(start.entities.len(), loaded.header.food.len());
+		assert_eq!(start.agents[&1].position, Position::new(5, 10));
+
+		// food respawned during the match is replayed too
+		assert!(loaded.steps.iter().any(|step| !step.food_spawned.is_empty()));
+		let reconstructed = loaded.engine_at(loaded.len()).unwrap();
+		assert!(reconstructed.game_concluded);
+		assert!(!loaded.engine_at(loaded.len() - 1).unwrap().game_concluded);
+		assert_eq!(reconstructed.round, 7);
+		assert_eq!(reconstructed.entities.len(), engine.entities.len());
+		for (id, agent) in &engine.agents {
+			assert_eq!(reconstructed.agents[id].position, agent.position);
+			assert_eq!(reconstructed.agents[id].stats, agent.stats);
+			assert_eq!(reconstructed.agents[id].genome.as_ref().map(Genome::weights), agent.genome.as_ref().map(Genome::weights));
+		}
+	}
+
+	#[test]
+	fn replays_partial_actions() {
+		let mut engine = Engine::new(EngineConfig {
+			size: [20f64, 20f64],
+			round_max: 40,
+			agent_count: 2,
+			..EngineConfig::default()
+		}).unwrap();
+		engine.record_replay = true;
+		let agents = (0..2u64)
+			.map(|id| (id, Agent::new(id, Position::new(id as i32 * 10, 10), Genome::blank(id))))
+			.collect::<HashMap<u64, Agent>>();
+		engine.begin_match(agents, 0);
+
+		// only agent 0 acts; agent 1 sits every step out
+		for _ in 0..5 {
+			engine.step_with(HashMap::from([(0, Action::Move(Direction::Right))])).unwrap();
+		}
+		let replay = engine.replay.take().unwrap();
+		assert!(replay.steps.iter().all(|step| step.agents[1].action.is_none()));
+
+		let reconstructed = replay.engine_at(replay.len()).unwrap();
+		for (id, agent) in &engine.agents {
+			assert_eq!(reconstructed.agents[id].position, agent.position);
+			assert_eq!(reconstructed.agents[id].stats, agent.stats);
+			assert_eq!(reconstructed.agents[id].action_counts, agent.action_counts);
+		}
+		assert_eq!(reconstructed.agents[&1].stats.steps_taken, 0);
+
+		// the recording stops 35 rounds short of round_max, so the match isn't over
+		assert!(!reconstructed.game_concluded);
+	}
+}
@@ -0,0 +1,64 @@
+use rand::Rng;
+
+// Bridson's Poisson-disc sampling over [0, size[0]) x [0, size[1]): points at least `rmin` apart,
+// trying `attempts` candidates around each point before retiring it. Unlike poisson-diskus, the
+// active list is a Vec, so the same generator state always gives the same points.
+pub fn poisson_disc<R: Rng>(size: [f64; 2], rmin: f64, attempts: usize, rng: &mut R) -> Vec<[f64; 2]> {
+	if rmin <= 0f64 || size[0] <= 0f64 || size[1] <= 0f64 {
+		return Vec::new();
+	}
+
+	let cell = rmin / 2f64.sqrt();
+	let columns = (size[0] / cell).ceil() as usize;
+	let rows = (size[1] / cell).ceil() as usize;
+	let mut grid: Vec<Option<usize>> = vec![None; columns * rows];
+	let grid_index = |point: &[f64; 2]| {
+		let column = ((point[0] / cell) as usize).min(columns - 1);
+		let row = ((point[1] / cell) as usize).min(rows - 1);
+		(column, row)
+	};
+
+	let first = [rng.gen_range(0f64..size[0]), rng.gen_range(0f64..size[1])];
+	let (column, row) = grid_index(&first);
+	grid[row * columns + column] = Some(0);
+	let mut samples = vec![first];
+	let mut active = vec![0usize];
+
+	while !active.is_empty() {
+		let active_idx = rng.gen_range(0..active.len());
+		let origin = samples[active[active_idx]];
+
+		let candidate = (0..attempts).find_map(|_| {
+			let radius = rng.gen_range(rmin..2f64 * rmin);
+			let angle = rng.gen_range(0f64..std::f64::consts::TAU);
+			let point = [origin[0] + radius * angle.cos(), origin[1] + radius * angle.sin()];
+			if point[0] < 0f64 || point[0] >= size[0] || point[1] < 0f64 || point[1] >= size[1] {
+				return None;
+			}
+
+			let (column, row) = grid_index(&point);
+			let too_close = (row.saturating_sub(2)..(row + 3).min(rows))
+				.flat_map(|row| (column.saturating_sub(2)..(column + 3).min(columns)).map(move |column| (column, row)))
+				.filter_map(|(column, row)| grid[row * columns + column])
+				.any(|idx| {
+					let other = samples[idx];
+					(other[0] - point[0]).powi(2) + (other[1] - point[1]).powi(2) < rmin * rmin
+				});
+			if too_close { None } else { Some(point) }
+		});
+
+		match candidate {
+			Some(point) => {
+				let (column, row) = grid_index(&point);
+				grid[row * columns + column] = Some(samples.len());
+				active.push(samples.len());
+				samples.push(point);
+			}
+			None => {
+				active.swap_remove(active_idx);
+			}
+		}
+	}
+
+	samples
+}
@@ -0,0 +1,311 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::backend::config::ExperimentConfig;
+use crate::backend::error::{Error, Problems, Result};
+use crate::backend::metrics::GenerationMetrics;
+use crate::backend::random;
+use crate::backend::Orchestrator;
+
+/// The values one swept parameter takes: listed explicitly, or drawn uniformly from a range
+/// (random search only). Range draws are rounded when the parameter is an integer.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ParameterSpace {
+	/// each of these in turn, or one at random per sample
+	Values(Vec<Value>),
+	/// uniform draws from [min, max]
+	Range { min: f64, max: f64 }
+}
+
+/// A set of experiments that differ from `base` in the swept parameters, each run once per seed.
+/// Loaded like ExperimentConfig, with the base experiment in a `[base]` table.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SweepConfig {
+	/// every point is run once with each of these seeds
+	pub seeds: Vec<u64>,
+	/// random points to draw; every combination of the listed values when unset
+	pub samples: Option<usize>,
+	/// seeds the draw of random points, independently of the runs' own seeds
+	pub sample_seed: u64,
+	/// runs are written to `base.output_dir/run_<index>`, next to summary.csv
+	pub base: ExperimentConfig,
+	/// keyed by dotted path into the experiment, e.g. "engine.food_spread", "engine.agent_count"
+	/// or "evolution.mutation.0.sigma"
+	pub parameters: BTreeMap<String, ParameterSpace>
+}
+
+impl Default for SweepConfig {
+	fn default() -> Self {
+		Self {
+			seeds: vec![0],
+			samples: None,
+			sample_seed: 0,
+			base: ExperimentConfig::default(),
+			parameters: BTreeMap::new()
+		}
+	}
+}
+
+/// The value every swept parameter takes in one experiment, in path order.
+pub type Point = Vec<(String, Value)>;
+
+/// One experiment of a sweep.
+#[derive(Debug, Clone)]
+pub struct SweepRun {
+	/// position in the sweep, naming its `run_<index>` directory
+	pub index: usize,
+	/// the experiment's seed
+	pub seed: u64,
+	/// the swept parameters' values
+	pub point: Point,
+	/// the base experiment with the point applied
+	pub experiment: ExperimentConfig
+}
+
+/// How one run of a sweep ended, as written to summary.csv.
+#[derive(Debug, Clone)]
+pub struct SweepResult {
+	/// the run this is the result of
+	pub run: SweepRun,
+	/// the run's last generation
+	pub metrics: GenerationMetrics,
+	/// food found by the final population's best genome, benchmarked with the evaluation policy
+	pub benchmark_food: f64
+}
+
+fn invalid(message: String) -> Error {
+	Error::InvalidConfig(message)
+}
+
+// Sets the field at `path` in a serialised experiment, keeping integer fields integers.
+fn apply(experiment: &mut Value, path: &str, value: &Value) -> Result<()> {
+	let mut target = experiment;
+	for key in path.split('.') {
+		target = match target {
+			Value::Object(fields) => fields.get_mut(key),
+			Value::Array(items) => key.parse::<usize>().ok().and_then(|idx| items.get_mut(idx)),
+			_ => None
+		}.ok_or_else(|| invalid(format!("{} is not a field of the experiment", path)))?;
+	}
+
+	*target = match value.as_f64() {
+		Some(number) if target.is_u64() && !value.is_u64() => Value::from(number.round().max(0f64) as u64),
+		Some(number) if target.is_i64() && !value.is_i64() => Value::from(number.round() as i64),
+		_ => value.clone()
+	};
+	Ok(())
+}
+
+impl SweepConfig {
+	/// Reads and validates a sweep, TOML unless the file ends in .json.
+	pub fn load(path: &Path) -> Result<Self> {
+		let contents = fs::read_to_string(path)?;
+		let sweep: SweepConfig = if path.extension().is_some_and(|extension| extension == "json") {
+			serde_json::from_str(&contents)?
+		} else {
+			toml::from_str(&contents)?
+		};
+		sweep.validate()?;
+		Ok(sweep)
+	}
+
+	/// Rejects sweeps that can't be sampled, naming every offending parameter. Each run's experiment
+	/// is validated separately, see `runs`.
+	pub fn validate(&self) -> Result<()> {
+		let mut problems = Problems::default();
+		problems.check(self.base.islands.is_none(), "sweeps evolve a single population, so base.islands can't be set");
+		for (path, space) in &self.parameters {
+			if let ParameterSpace::Range { min, max } = space {
+				problems.check(min.is_finite() && max.is_finite() && min <= max, &format!("{} must have finite bounds with min no greater than max", path));
+			}
+		}
+		problems.into_result()
+	}
+
+	/// Every parameter point to run.
+	pub fn points(&self) -> Result<Vec<Point>> {
+		self.validate()?;
+		let Some(samples) = self.samples else {
+			let mut points = vec![Vec::new()];
+			for (path, space) in &self.parameters {
+				let ParameterSpace::Values(values) = space else {
+					return Err(invalid(format!("{} is a range, which needs `samples` set", path)));
+				};
+				points = points.into_iter()
+					.flat_map(|point| values.iter().map(move |value| {
+						let mut point = point.clone();
+						point.push((path.clone(), value.clone()));
+						point
+					}))
+					.collect();
+			}
+			return Ok(points);
+		};
+
+		let mut rng = StdRng::seed_from_u64(self.sample_seed);
+		(0..samples)
+			.map(|_| {
+				self.parameters.iter()
+					.map(|(path, space)| {
+						let value = match space {
+							ParameterSpace::Values(values) => values.choose(&mut rng)
+								.cloned()
+								.ok_or_else(|| invalid(format!("{} has no values", path)))?,
+							ParameterSpace::Range { min, max } => Value::from(rng.gen_range(*min..=*max))
+						};
+						Ok((path.clone(), value))
+					})
+					.collect()
+			})
+			.collect()
+	}
+
+	/// The validated experiment of every run, points outermost and seeds innermost.
+	pub fn runs(&self) -> Result<Vec<SweepRun>> {
+		let base = serde_json::to_value(&self.base)?;
+		let mut runs = Vec::new();
+		for point in self.points()? {
+			let mut experiment = base.clone();
+			for (path, value) in &point {
+				apply(&mut experiment, path, value)?;
+			}
+			let experiment: ExperimentConfig = serde_json::from_value(experiment)?;
+
+			for seed in &self.seeds {
+				let index = runs.len();
+				let experiment = ExperimentConfig {
+					seed: Some(*seed),
+					output_dir: self.base.output_dir.as_ref().map(|output_dir| output_dir.join(format!("run_{}", index))),
+					..experiment.clone()
+				};
+				experiment.validate()
+					.map_err(|error| invalid(format!("run {}: {}", index, error)))?;
+
+				runs.push(SweepRun {
+					index,
+					seed: *seed,
+					point: point.clone(),
+					experiment
+				});
+			}
+		}
+		Ok(runs)
+	}
+
+	/// Plays every run in turn, rewriting summary.csv in the output directory after each one so an
+	/// interrupted sweep keeps what it finished. `before_run` is handed each run, the number of runs
+	/// and the run's orchestrator before it starts, to report progress or attach metrics sinks.
+	pub fn run(&self, mut before_run: impl FnMut(&SweepRun, usize, &mut Orchestrator)) -> Result<Vec<SweepResult>> {
+		let runs = self.runs()?;
+		let run_count = runs.len();
+		let mut results = Vec::with_capacity(run_count);
+		for run in runs {
+			random::seed(run.seed);
+
+			let mut orchestrator = Orchestrator::with_config(run.experiment.engine.clone(), run.experiment.orchestrator_config())?;
+			before_run(&run, run_count, &mut orchestrator);
+			let population = orchestrator.evolve(&run.experiment, 0, Vec::new())?;
+			let (_, best) = orchestrator.finish(&population)?;
+
+			results.push(SweepResult {
+				metrics: orchestrator.latest_metrics().cloned().unwrap(),
+				benchmark_food: best.food,
+				run
+			});
+			if let Some(output_dir) = &self.base.output_dir {
+				write_summary(&results, &output_dir.join("summary.csv"))?;
+			}
+		}
+		Ok(results)
+	}
+}
+
+// "path=value" for every parameter of a point.
+pub fn describe(point: &[(String, Value)]) -> String {
+	point.iter()
+		.map(|(path, value)| format!("{}={}", path, value))
+		.collect::<Vec<String>>()
+		.join(" ")
+}
+
+fn csv_field(value: String) -> String {
+	if value.contains([',', '"', '\n']) {
+		format!("\"{}\"", value.replace('"', "\"\""))
+	} else {
+		value
+	}
+}
+
+// One row per run: its index, seed and parameter values, then its final GenerationMetrics and
+// benchmark. Every run of a sweep shares the same parameters, so the first one names the columns.
+pub fn write_summary(results: &[SweepResult], path: &Path) -> Result<()> {
+	let mut writer = BufWriter::new(File::create(path)?);
+	let parameters = results.first()
+		.map(|result| result.run.point.iter().map(|(path, _)| path.clone()).collect::<Vec<String>>())
+		.unwrap_or_default();
+
+	let header = ["run".to_string(), "seed".to_string()].into_iter()
+		.chain(parameters)
+		.chain(GenerationMetrics::FIELDS.iter().map(|field| field.to_string()))
+		.chain(["benchmark_food".to_string()])
+		.map(csv_field)
+		.collect::<Vec<String>>();
+	writeln!(writer, "{}", header.join(","))?;
+
+	for result in results {
+		let row = [result.run.index.to_string(), result.run.seed.to_string()].into_iter()
+			.chain(result.run.point.iter().map(|(_, value)| value.to_string()))
+			.chain(result.metrics.values())
+			.chain([result.benchmark_food.to_string()])
+			.map(csv_field)
+			.collect::<Vec<String>>();
+		writeln!(writer, "{}", row.join(","))?;
+	}
+	Ok(writer.flush()?)
+}
+
+// Benchmark food of every parameter point, averaged over its seeds.
+#[derive(Debug, Clone)]
+pub struct PointSummary {
+	pub point: Point,
+	pub runs: usize,
+	pub mean_benchmark_food: f64,
+	pub std_benchmark_food: f64,
+	pub mean_best_fitness: f64
+}
+
+// Groups results by parameter point, best mean benchmark first.
+pub fn summarise(results: &[SweepResult]) -> Vec<PointSummary> {
+	let mut groups: Vec<(Point, Vec<&SweepResult>)> = Vec::new();
+	for result in results {
+		match groups.iter_mut().find(|(point, _)| *point == result.run.point) {
+			Some((_, group)) => group.push(result),
+			None => groups.push((result.run.point.clone(), vec![result]))
+		}
+	}
+
+	let mut summaries = groups.into_iter()
+		.map(|(point, group)| {
+			let runs = group.len() as f64;
+			let mean = group.iter().map(|result| result.benchmark_food).sum::<f64>() / runs;
+			let variance = group.iter().map(|result| (result.benchmark_food - mean).powi(2)).sum::<f64>() / runs;
+			PointSummary {
+				point,
+				runs: group.len(),
+				mean_benchmark_food: mean,
+				std_benchmark_food: variance.sqrt(),
+				mean_best_fitness: group.iter().map(|result| result.metrics.best_fitness).sum::<f64>() / runs
+			}
+		})
+		.collect::<Vec<PointSummary>>();
+	summaries.sort_by(|a, b| b.mean_benchmark_food.total_cmp(&a.mean_benchmark_food));
+	summaries
+}
@@ -0,0 +1,77 @@
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod sweep_tests {
+	use crate::backend::error::Error;
+	use crate::backend::mutation::MutationOperator;
+	use crate::backend::sweep::{ParameterSpace, SweepConfig};
+
+	fn sweep(parameters: &str) -> SweepConfig {
+		toml::from_str(&format!("
+			seeds = [1, 2]
+
+			[base]
+			generations = 1
+
+			[base.engine]
+			size = [20.0, 20.0]
+			round_max = 20
+			agent_count = 6
+
+			[base.evolution]
+			trials = 1
+			mutation = [{{ type = \"gaussian\", sigma = 0.1, probability = 1.0 }}]
+
+			{}
+		", parameters)).unwrap()
+	}
+
+	#[test]
+	fn grid_covers_every_combination_and_seed() {
+		let sweep = sweep("
+			[parameters]
+			\"engine.food_spread\" = [1.5, 3.0]
+			\"engine.vision_radius\" = [1, 2, 3]
+			\"evolution.mutation.0.sigma\" = [0.05]
+		");
+		let runs = sweep.runs().unwrap();
+		assert_eq!(runs.len(), 2 * 3 * 2);
+
+		let last = runs.last().unwrap();
+		assert_eq!(last.seed, 2);
+		assert_eq!(last.experiment.seed, Some(2));
+		assert_eq!(last.experiment.engine.food_spread, 3.0);
+		assert_eq!(last.experiment.engine.vision_radius, 3);
+		assert_eq!(last.experiment.evolution.mutation[0], MutationOperator::Gaussian { sigma: 0.05, probability: 1.0 });
+		assert_eq!(last.experiment.engine.agent_count, 6);
+	}
+
+	#[test]
+	fn random_samples_stay_in_range() {
+		let mut sweep = sweep("
+			[parameters]
+			\"engine.agent_count\" = { min = 6, max = 12 }
+			\"engine.food_spread\" = { min = 1.0, max = 4.0 }
+		");
+		assert!(sweep.runs().is_err(), "ranges can't be gridded");
+
+		sweep.samples = Some(5);
+		let runs = sweep.runs().unwrap();
+		assert_eq!(runs.len(), 5 * 2);
+		for run in &runs {
+			assert!((6..=12).contains(&run.experiment.engine.agent_count));
+			assert!((1.0..=4.0).contains(&run.experiment.engine.food_spread));
+		}
+
+		sweep.parameters.clear();
+		sweep.parameters.insert("engine.food_spred".to_string(), ParameterSpace::Values(vec![serde_json::json!(1.0)]));
+		assert!(sweep.runs().is_err(), "unknown fields are rejected");
+
+		// empty and NaN ranges can't be sampled
+		for (min, max) in [(4.0, 1.0), (f64::NAN, 1.0), (1.0, f64::INFINITY)] {
+			sweep.parameters.clear();
+			sweep.parameters.insert("engine.food_spread".to_string(), ParameterSpace::Range { min, max });
+			assert!(matches!(sweep.validate(), Err(Error::InvalidConfig(_))), "{} {}", min, max);
+			assert!(sweep.runs().is_err());
+		}
+	}
+}
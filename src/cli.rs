@@ -0,0 +1,319 @@
+use std::path::{Path, PathBuf};
+use clap::{Parser, Subcommand};
+use rand::RngCore;
+use crate::backend::archive::GenomeRecord;
+use crate::backend::baseline::Baseline;
+use crate::backend::checkpoint::Checkpoint;
+use crate::backend::config::ExperimentConfig;
+use crate::backend::error::{Error, Result};
+use crate::backend::gene::Genome;
+use crate::backend::island::{Archipelago, IslandConfig};
+use crate::backend::metrics::{GenerationMetrics, MetricsSink};
+use crate::backend::network::NetworkShape;
+use crate::backend::random;
+use crate::backend::replay::Replay;
+use crate::backend::sweep::{self, SweepConfig};
+use crate::backend::Orchestrator;
+use crate::interface::render::FrameRenderer;
+use crate::interface::Visualiser;
+
+/// The `cadmus` command line; parse it with `Cli::parse` and call `run`.
+#[derive(Debug, Parser)]
+#[command(name = "cadmus", about = "Evolves foraging agents and inspects the results", long_about = None)]
+pub struct Cli {
+	// evolves with the default experiment when omitted
+	#[command(subcommand)]
+	command: Option<Command>
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+	/// Evolve a population from scratch
+	Evolve {
+		/// Experiment config file (TOML, or JSON if it ends in .json)
+		#[arg(short, long)]
+		config: Option<PathBuf>,
+		#[arg(short, long)]
+		seed: Option<u64>,
+		/// Directory for checkpoints, metrics and other run artefacts
+		#[arg(short, long)]
+		output: Option<PathBuf>,
+		#[arg(short, long)]
+		generations: Option<usize>
+	},
+	/// Evolve once per seed and hyperparameter point of a sweep, collecting a summary table
+	Sweep {
+		/// Sweep config file: seeds, samples, a [base] experiment and the [parameters] to vary
+		config: PathBuf,
+		/// Directory for the runs and summary.csv, instead of the base experiment's
+		#[arg(short, long)]
+		output: Option<PathBuf>
+	},
+	/// Carry on a run from a checkpoint
+	Resume {
+		checkpoint: PathBuf,
+		/// Total generations to reach, instead of the checkpointed experiment's
+		#[arg(short, long)]
+		generations: Option<usize>
+	},
+	/// Benchmark a saved genome with the evaluation policy, next to the scores of the scripted baselines.
+	/// The world comes from the config, seen with the vision radius the genome evolved with
+	Eval {
+		genome: PathBuf,
+		#[arg(short, long)]
+		config: Option<PathBuf>,
+		#[arg(short, long)]
+		seed: Option<u64>,
+		#[arg(short, long, default_value_t = 10)]
+		trials: usize
+	},
+	/// Watch a live match in the terminal, between a checkpoint's last population or fresh genomes
+	Watch {
+		/// Checkpoint whose last population plays, in its own world and with its own seed
+		#[arg(long, conflicts_with_all = ["config", "seed"])]
+		checkpoint: Option<PathBuf>,
+		/// Experiment config file for a match between fresh genomes
+		#[arg(short, long)]
+		config: Option<PathBuf>,
+		#[arg(short, long)]
+		seed: Option<u64>
+	},
+	/// Watch a recorded match in the terminal, or render it to images
+	Replay {
+		file: PathBuf,
+		/// Write an animated GIF here instead of opening the viewer
+		#[arg(long)]
+		gif: Option<PathBuf>,
+		/// Write PNG frames into this directory instead of opening the viewer
+		#[arg(long)]
+		frames: Option<PathBuf>,
+		/// Render every nth round
+		#[arg(long, default_value_t = 5)]
+		every: usize
+	},
+	/// List the genomes in a genome archive (.jsonl) or checkpoint
+	Inspect {
+		pool: PathBuf,
+		/// Write this genome to a standalone genome file, see --export
+		#[arg(long, requires = "export")]
+		genome: Option<u64>,
+		#[arg(long)]
+		export: Option<PathBuf>
+	}
+}
+
+impl Cli {
+	/// Runs the chosen subcommand, evolving the default experiment when there is none.
+	pub fn run(self) -> Result<()> {
+		let command = self.command.unwrap_or(Command::Evolve {
+			config: None,
+			seed: None,
+			output: None,
+			generations: None
+		});
+
+		match command {
+			Command::Evolve { config, seed, output, generations } => {
+				let mut experiment = load_experiment(config.as_deref())?;
+				experiment.seed = seed.or(experiment.seed);
+				experiment.output_dir = output.or(experiment.output_dir);
+				experiment.generations = generations.unwrap_or(experiment.generations);
+				experiment.validate()?;
+				if let Some(seed) = experiment.seed {
+					random::seed(seed);
+				}
+
+				if let Some(islands) = &experiment.islands {
+					return evolve_islands(&experiment, islands.clone());
+				}
+				let mut orchestrator = Orchestrator::with_config(experiment.engine.clone(), experiment.orchestrator_config())?;
+				orchestrator.add_metrics_sink(Box::new(Progress::default()));
+				let population = orchestrator.evolve(&experiment, 0, Vec::new())?;
+				finish(&orchestrator, &population)
+			}
+			Command::Sweep { config, output } => {
+				let mut sweep = SweepConfig::load(&config)?;
+				sweep.base.output_dir = output.or(sweep.base.output_dir);
+
+				let results = sweep.run(|run, run_count, orchestrator| {
+					println!("run {} of {}: seed {} {}", run.index + 1, run_count, run.seed, sweep::describe(&run.point));
+					orchestrator.add_metrics_sink(Box::new(Progress::default()));
+				})?;
+				println!("{:>10}  {:>8}  {:>12}  {:>12}  parameters", "runs", "food", "food std", "fitness");
+				for summary in sweep::summarise(&results) {
+					println!("{:>10}  {:>8.2}  {:>12.2}  {:>12.2}  {}",
+						summary.runs,
+						summary.mean_benchmark_food,
+						summary.std_benchmark_food,
+						summary.mean_best_fitness,
+						sweep::describe(&summary.point));
+				}
+				Ok(())
+			}
+			Command::Resume { checkpoint, generations } => {
+				let checkpoint = Checkpoint::load(&checkpoint)?;
+				let mut experiment = checkpoint.experiment.clone();
+				experiment.generations = generations.unwrap_or(experiment.generations);
+				let (start, population) = (checkpoint.generation, checkpoint.population.clone());
+
+				let mut orchestrator = Orchestrator::resume(checkpoint)?;
+				orchestrator.add_metrics_sink(Box::new(Progress::default()));
+				let population = orchestrator.evolve(&experiment, start, population)?;
+				finish(&orchestrator, &population)
+			}
+			Command::Eval { genome, config, seed, trials } => {
+				let mut experiment = load_experiment(config.as_deref())?;
+				if let Some(seed) = seed.or(experiment.seed) {
+					random::seed(seed);
+				}
+
+				let record = GenomeRecord::load(&genome)?;
+				if let Some(vision_radius) = record.vision_radius {
+					experiment.engine.vision_radius = vision_radius;
+				}
+				let genome = record.to_genome(NetworkShape { inputs: experiment.engine.vision_tiles(), hidden: record.shape.hidden })?;
+				let id = genome.id;
+				let mut orchestrator = Orchestrator::with_config(experiment.engine.clone(), experiment.orchestrator_config())?;
+				orchestrator.genepool.add_genome(genome);
+
+				let evaluation = &orchestrator.benchmark(&[id], trials)?[&id];
+				println!("genome {}: {:.2} food over {} trials (variance {:.2})", id, evaluation.food, trials, evaluation.variance);
+				for baseline in Baseline::ALL {
+					let evaluation = orchestrator.benchmark_baseline(baseline, trials)?;
+					println!("{:>20}: {:.2} food (variance {:.2})", baseline.name(), evaluation.food, evaluation.variance);
+				}
+				Ok(())
+			}
+			Command::Watch { checkpoint, config, seed } => {
+				let (orchestrator, population) = match checkpoint {
+					Some(checkpoint) => {
+						let checkpoint = Checkpoint::load(&checkpoint)?;
+						let population = checkpoint.population.clone();
+						(Orchestrator::resume(checkpoint)?, population)
+					}
+					None => {
+						let experiment = load_experiment(config.as_deref())?;
+						if let Some(seed) = seed.or(experiment.seed) {
+							random::seed(seed);
+						}
+						let mut orchestrator = Orchestrator::with_config(experiment.engine.clone(), experiment.orchestrator_config())?;
+						let shape = NetworkShape { inputs: experiment.engine.vision_tiles(), hidden: experiment.evolution.hidden_nodes };
+						let population = (0..experiment.engine.agent_count)
+							.map(|_| {
+								let genome = Genome::new(random::rng().next_u64(), shape);
+								let id = genome.id;
+								orchestrator.genepool.add_genome(genome);
+								id
+							})
+							.collect::<Vec<u64>>();
+						(orchestrator, population)
+					}
+				};
+
+				let engine = orchestrator.begin_match(&population, orchestrator.config.evaluation_policy)?;
+				Visualiser::new(engine).run()?;
+				Ok(())
+			}
+			Command::Replay { file, gif, frames, every } => {
+				let replay = Replay::load(&file)?;
+				if gif.is_none() && frames.is_none() {
+					Visualiser::from_replay(replay)?.run()?;
+					return Ok(());
+				}
+
+				let images = FrameRenderer::default().render_replay(&replay, every)?;
+				if let Some(gif) = gif {
+					FrameRenderer::save_gif(&images, &gif, 80)?;
+				}
+				if let Some(frames) = frames {
+					FrameRenderer::save_frames(&images, &frames)?;
+				}
+				Ok(())
+			}
+			Command::Inspect { pool, genome, export } => inspect(&pool, genome, export.as_deref())
+		}
+	}
+}
+
+fn load_experiment(path: Option<&Path>) -> Result<ExperimentConfig> {
+	match path {
+		Some(path) => ExperimentConfig::load(path),
+		None => Ok(ExperimentConfig::default())
+	}
+}
+
+fn finish(orchestrator: &Orchestrator, population: &[u64]) -> Result<()> {
+	// empty when no generations were run
+	if !population.is_empty() {
+		let (_, best) = orchestrator.finish(population)?;
+		println!("benchmark: {:.2} (variance {:.2})", best.food, best.variance);
+	}
+	Ok(())
+}
+
+// Island runs aren't checkpointed; each island writes its own artefacts to island_<n>.
+fn evolve_islands(experiment: &ExperimentConfig, islands: IslandConfig) -> Result<()> {
+	let mut archipelago = Archipelago::new(experiment.engine.clone(), experiment.orchestrator_config(), islands)?;
+	for (island, orchestrator) in archipelago.islands.iter_mut().enumerate() {
+		orchestrator.add_metrics_sink(Box::new(Progress { island: Some(island) }));
+	}
+	if let Some(output_dir) = &experiment.output_dir {
+		experiment.save(&output_dir.join("config.toml"))?;
+	}
+
+	let populations = archipelago.run(experiment.generations)?;
+	for (island, (_, best)) in archipelago.finish(&populations)?.iter().enumerate() {
+		println!("island {} benchmark: {:.2} (variance {:.2})", island, best.food, best.variance);
+	}
+	Ok(())
+}
+
+// Prints a line per generation as a run goes.
+#[derive(Default)]
+struct Progress {
+	island: Option<usize>
+}
+
+impl MetricsSink for Progress {
+	fn record(&mut self, metrics: &GenerationMetrics) -> Result<()> {
+		if let Some(island) = self.island {
+			print!("island {} ", island);
+		}
+		println!("generation {}: best food {:.2}, mean fitness {:.2}, {} species",
+			metrics.generation,
+			metrics.best_food,
+			metrics.mean_fitness,
+			metrics.species);
+		Ok(())
+	}
+}
+
+fn inspect(pool: &Path, genome: Option<u64>, export: Option<&Path>) -> Result<()> {
+	let records = if pool.extension().is_some_and(|extension| extension == "jsonl") {
+		let contents = std::fs::read_to_string(pool)?;
+		contents.lines()
+			.map(|line| serde_json::from_str(line).map_err(Error::from))
+			.collect::<Result<Vec<GenomeRecord>>>()?
+	} else {
+		let checkpoint = Checkpoint::load(pool)?;
+		println!("checkpoint after {} generations, {} in the last population", checkpoint.generation, checkpoint.population.len());
+		checkpoint.genomes
+	};
+
+	if let (Some(id), Some(export)) = (genome, export) {
+		let record = records.iter()
+			.find(|record| record.id == id)
+			.ok_or(Error::UnknownGenome(id))?;
+		return record.save(export);
+	}
+
+	println!("{:>20}  {:>20}  {:>9}  {:>9}  mutation", "id", "parent", "mean |w|", "step size");
+	for record in &records {
+		let mean_weight = record.weights.iter().map(|weight| weight.abs()).sum::<f32>() / record.weights.len().max(1) as f32;
+		let step_size = record.step_sizes.iter().sum::<f32>() / record.step_sizes.len().max(1) as f32;
+		let parent = record.parents.first().map_or("-".to_string(), |parent| parent.to_string());
+		println!("{:>20}  {:>20}  {:>9.4}  {:>9.4}  {}", record.id, parent, mean_weight, step_size, record.mutation.as_deref().unwrap_or("-"));
+	}
+	println!("{} genomes", records.len());
+	Ok(())
+}
@@ -0,0 +1,101 @@
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod cli_tests {
+	use std::fs;
+	use std::path::{Path, PathBuf};
+	use clap::Parser;
+	use crate::cli::Cli;
+
+	const EXPERIMENT: &str = "
+		generations = 2
+		checkpoint_interval = 1
+
+		[engine]
+		size = [20.0, 20.0]
+		round_max = 20
+		agent_count = 6
+		vision_radius = 2
+
+		[evolution]
+		trials = 1
+		threads = 2
+	";
+
+	// A fresh directory holding the test experiment as experiment.toml.
+	fn run_dir(name: &str) -> PathBuf {
+		let directory = std::env::temp_dir().join(format!("cadmus_cli_{}_{}", name, std::process::id()));
+		let _ = fs::remove_dir_all(&directory);
+		fs::create_dir_all(&directory).unwrap();
+		fs::write(directory.join("experiment.toml"), EXPERIMENT).unwrap();
+		directory
+	}
+
+	fn run(args: &[&str]) {
+		Cli::try_parse_from(["cadmus"].iter().chain(args)).unwrap().run().unwrap();
+	}
+
+	fn path(path: &Path) -> &str {
+		path.to_str().unwrap()
+	}
+
+	#[test]
+	fn evolve_then_resume_eval_inspect_and_replay() {
+		let directory = run_dir("evolve");
+		let output = directory.join("run");
+		run(&["evolve", "--config", path(&directory.join("experiment.toml")), "--seed", "3", "--output", path(&output)]);
+		for artefact in ["config.toml", "checkpoint.json", "best_genome.json", "replay.jsonl", "genomes.jsonl", "metrics.csv"] {
+			assert!(output.join(artefact).exists(), "{} missing", artefact);
+		}
+
+		run(&["resume", path(&output.join("checkpoint.json")), "--generations", "3"]);
+		let checkpoint: serde_json::Value = serde_json::from_str(&fs::read_to_string(output.join("checkpoint.json")).unwrap()).unwrap();
+		assert_eq!(checkpoint["generation"], 3);
+
+		run(&["eval", path(&output.join("best_genome.json")), "--config", path(&directory.join("experiment.toml")), "--trials", "2"]);
+		// the genome file brings its own vision radius, so the default world fits it too
+		run(&["eval", path(&output.join("best_genome.json")), "--trials", "1"]);
+
+		let genome = fs::read_to_string(output.join("genomes.jsonl")).unwrap()
+			.lines()
+			.next()
+			.map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap()["id"].to_string())
+			.unwrap();
+		let exported = directory.join("genome.json");
+		run(&["inspect", path(&output.join("genomes.jsonl")), "--genome", &genome, "--export", path(&exported)]);
+		assert!(exported.exists());
+		run(&["inspect", path(&output.join("checkpoint.json"))]);
+
+		let frames = directory.join("frames");
+		run(&["replay", path(&output.join("replay.jsonl")), "--frames", path(&frames), "--every", "10"]);
+		assert!(fs::read_dir(&frames).unwrap().count() > 0);
+
+		fs::remove_dir_all(&directory).unwrap();
+	}
+
+	#[test]
+	fn evolve_islands() {
+		let directory = run_dir("islands");
+		let config = directory.join("experiment.toml");
+		fs::write(&config, format!("{}\n[islands]\nislands = 2\nmigration_interval = 1\nmigrants = 1\n", EXPERIMENT)).unwrap();
+
+		let output = directory.join("run");
+		run(&["evolve", "--config", path(&config), "--output", path(&output)]);
+		assert!(output.join("config.toml").exists());
+		for island in ["island_0", "island_1"] {
+			assert!(output.join(island).join("best_genome.json").exists());
+		}
+
+		fs::remove_dir_all(&directory).unwrap();
+	}
+
+	#[test]
+	fn rejects_bad_arguments() {
+		assert!(Cli::try_parse_from(["cadmus", "resume"]).is_err());
+		assert!(Cli::try_parse_from(["cadmus", "inspect", "pool.jsonl", "--genome", "1"]).is_err());
+		assert!(Cli::try_parse_from(["cadmus", "evolve", "--generations", "many"]).is_err());
+		assert!(Cli::try_parse_from(["cadmus", "watch", "--checkpoint", "checkpoint.json", "--seed", "1"]).is_err());
+
+		let error = Cli::try_parse_from(["cadmus", "eval", "missing_genome.json"]).unwrap().run();
+		assert!(error.is_err());
+	}
+}
@@ -0,0 +1,229 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use crate::backend::agent::Agent;
+use crate::backend::engine::Engine;
+use crate::backend::error::Result;
+use crate::backend::map::Action;
+use crate::backend::replay::Replay;
+use crate::backend::Position;
+
+pub mod render;
+mod render_tests;
+
+const ACTION_NAMES: [&str; Action::COUNT] = ["up", "down", "left", "right", "reproduce"];
+
+/// Terminal view of a match in progress: the map around the selected agent on the left, the
+/// agent's stats, senses and action probabilities on the right.
+///
+/// space pauses, n steps once while paused, +/- change speed, tab/shift-tab change agent, q quits.
+pub struct Visualiser {
+	engine: Engine,
+	// when set, rounds are played back from the replay instead of simulated
+	replay: Option<Replay>,
+	paused: bool,
+	// time between rounds while playing
+	delay: Duration,
+	selected: usize
+}
+
+impl Visualiser {
+	const MIN_DELAY: Duration = Duration::from_millis(10);
+	const MAX_DELAY: Duration = Duration::from_millis(2000);
+
+	/// `engine` should already have a match begun, see Orchestrator::begin_match and Replay::engine_at.
+	pub fn new(engine: Engine) -> Self {
+		Self {
+			engine,
+			replay: None,
+			paused: false,
+			delay: Duration::from_millis(100),
+			selected: 0
+		}
+	}
+
+	/// Plays back `replay` from its first step instead of a live match.
+	pub fn from_replay(replay: Replay) -> Result<Self> {
+		let engine = replay.engine_at(0)?;
+		Ok(Self {
+			replay: Some(replay),
+			..Visualiser::new(engine)
+		})
+	}
+
+	/// Runs until the user quits and hands back the engine in whatever state it reached.
+	pub fn run(mut self) -> Result<Engine> {
+		let mut terminal = ratatui::init();
+		let result = self.event_loop(&mut terminal);
+		ratatui::restore();
+		result.map(|_| self.engine)
+	}
+
+	fn event_loop(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+		let mut last_step = Instant::now();
+		loop {
+			terminal.draw(|frame| self.draw(frame))?;
+
+			let timeout = if self.paused {
+				Visualiser::MAX_DELAY
+			} else {
+				self.delay.saturating_sub(last_step.elapsed())
+			};
+			if event::poll(timeout)? {
+				if let Event::Key(key) = event::read()? {
+					if key.kind == KeyEventKind::Press && self.handle_key(key.code)? {
+						return Ok(());
+					}
+				}
+			}
+
+			if !self.paused && last_step.elapsed() >= self.delay {
+				self.step()?;
+				last_step = Instant::now();
+			}
+		}
+	}
+
+	// Returns whether to quit.
+	fn handle_key(&mut self, code: KeyCode) -> Result<bool> {
+		let agent_count = self.engine.agents.len().max(1);
+		match code {
+			KeyCode::Char('q') | KeyCode::Esc => return Ok(true),
+			KeyCode::Char(' ') => self.paused = !self.paused,
+			KeyCode::Char('n') | KeyCode::Right if self.paused => self.step()?,
+			KeyCode::Char('+') | KeyCode::Char('=') => self.delay = (self.delay / 2).max(Visualiser::MIN_DELAY),
+			KeyCode::Char('-') => self.delay = (self.delay * 2).min(Visualiser::MAX_DELAY),
+			KeyCode::Tab => self.selected = (self.selected + 1) % agent_count,
+			KeyCode::BackTab => self.selected = (self.selected + agent_count - 1) % agent_count,
+			_ => {}
+		}
+		Ok(false)
+	}
+
+	fn step(&mut self) -> Result<()> {
+		if self.engine.game_concluded {
+			return Ok(());
+		}
+		match &self.replay {
+			Some(replay) => replay.apply_step(&mut self.engine),
+			None => self.engine.step()
+		}
+	}
+
+	fn selected_agent(&self) -> Option<&Agent> {
+		let mut ids = self.engine.agents.keys().cloned().collect::<Vec<u64>>();
+		ids.sort();
+		ids.get(self.selected).map(|id| &self.engine.agents[id])
+	}
+
+	fn draw(&self, frame: &mut Frame) {
+		let [map_area, panel_area] = Layout::horizontal([Constraint::Min(20), Constraint::Length(36)])
+			.areas(frame.area());
+		self.draw_map(frame, map_area);
+		self.draw_panel(frame, panel_area);
+	}
+
+	fn draw_map(&self, frame: &mut Frame, area: Rect) {
+		let state = if self.engine.game_concluded {
+			"finished"
+		} else if self.paused {
+			"paused"
+		} else {
+			"playing"
+		};
+		let block = Block::default()
+			.borders(Borders::ALL)
+			.title(format!(" round {}/{} - {} - {}ms ", self.engine.round_idx, self.engine.config.round_max, state, self.delay.as_millis()));
+		let inner = block.inner(area);
+
+		// keep the selected agent in the middle of the view, without scrolling past the map's edges
+		let size = (self.engine.config.size[0] as i32 + 1, self.engine.config.size[1] as i32 + 1);
+		let (width, height) = (inner.width as i32, inner.height as i32);
+		let centre = self.selected_agent().map_or(Position::new(size.0 / 2, size.1 / 2), |agent| agent.position);
+		let left = (centre.x - width / 2).clamp(0, (size.0 - width).max(0));
+		let top = (centre.y - height / 2).clamp(0, (size.1 - height).max(0));
+		let selected = self.selected_agent().map(|agent| agent.id);
+
+		let occupants = self.engine.agents.values()
+			.map(|agent| (agent.position, agent.id))
+			.collect::<HashMap<Position, u64>>();
+
+		let lines = (top..(top + height).min(size.1))
+			.map(|y| {
+				Line::from((left..(left + width).min(size.0))
+					.map(|x| {
+						let position = Position::new(x, y);
+						match occupants.get(&position) {
+							Some(id) if Some(*id) == selected => Span::styled("@", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+							Some(_) => Span::styled("@", Style::default().fg(Color::Cyan)),
+							None if self.engine.entities.contains_key(&position) => Span::styled("*", Style::default().fg(Color::Green)),
+							None => Span::styled(".", Style::default().fg(Color::DarkGray))
+						}
+					})
+					.collect::<Vec<Span>>())
+			})
+			.collect::<Vec<Line>>();
+
+		frame.render_widget(Paragraph::new(lines).block(block), area);
+	}
+
+	fn draw_panel(&self, frame: &mut Frame, area: Rect) {
+		let block = Block::default().borders(Borders::ALL).title(" agent ");
+		let Some(agent) = self.selected_agent() else {
+			frame.render_widget(Paragraph::new("no agents").block(block), area);
+			return;
+		};
+
+		let mut lines = vec![
+			Line::from(format!("id        {}", agent.id)),
+			Line::from(format!("position  {}, {}", agent.position.x, agent.position.y)),
+			Line::from(format!("food      {}", agent.stats.food_eaten)),
+			Line::from(format!("steps     {}", agent.stats.steps_taken)),
+			Line::from(format!("visited   {}", agent.visited.len())),
+			Line::from(""),
+			Line::from("sense")
+		];
+
+		match &agent.current_sense {
+			Some(sense) => {
+				for row in sense.map_tiles.chunks(self.engine.config.vision_length()) {
+					lines.push(Line::from(row.iter()
+						.map(|tile| if *tile {
+							Span::styled("* ", Style::default().fg(Color::Green))
+						} else {
+							Span::styled(". ", Style::default().fg(Color::DarkGray))
+						})
+						.collect::<Vec<Span>>()));
+				}
+
+				lines.push(Line::from(""));
+				if let Some(policy) = &agent.policy {
+					lines.push(Line::from(format!("scripted  {:?}", policy)));
+				} else if let Some(genome) = &agent.genome {
+					lines.push(Line::from("action probabilities"));
+					let probabilities = genome.action_probabilities(&sense.input());
+					for (name, probability) in ACTION_NAMES.iter().zip(probabilities) {
+						let bar = "#".repeat((probability * 20f32).round() as usize);
+						lines.push(Line::from(format!("{:<10}{:>5.2} {}", name, probability, bar)));
+					}
+				} else {
+					lines.push(Line::from("idle"));
+				}
+			}
+			None => lines.push(Line::from("(not sensed yet)"))
+		}
+
+		lines.push(Line::from(""));
+		lines.push(Line::from("actions taken"));
+		for (name, count) in ACTION_NAMES.iter().zip(agent.action_counts) {
+			lines.push(Line::from(format!("{:<10}{}", name, count)));
+		}
+
+		frame.render_widget(Paragraph::new(lines).block(block), area);
+	}
+}
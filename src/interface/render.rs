@@ -0,0 +1,215 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fs::File;
+use std::hash::{Hash, Hasher};
+use std::io::BufWriter;
+use std::path::Path;
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, Frame, Rgba, RgbaImage};
+use crate::backend::agent::Agent;
+use crate::backend::engine::Engine;
+use crate::backend::error::Result;
+use crate::backend::gene::Genome;
+use crate::backend::metrics::assign_species;
+use crate::backend::replay::Replay;
+use crate::backend::Position;
+
+/// How FrameRenderer groups agents into colours.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Colouring {
+	/// agents sharing a parent share a colour
+	Lineage,
+	/// agents whose weights are within `threshold` RMS distance share a colour, see GenerationMetrics
+	Species { threshold: f64 }
+}
+
+/// Draws engine states as images, without needing a terminal or display. Food is drawn green on a
+/// background shaded by how much food is nearby; agents are drawn over it in their group's colour.
+#[derive(Debug, Clone)]
+pub struct FrameRenderer {
+	/// pixels per tile
+	pub scale: u32,
+	/// how agents are grouped into colours
+	pub colouring: Colouring,
+	/// tiles either side of a tile counted towards its food density
+	pub density_radius: i32
+}
+
+impl Default for FrameRenderer {
+	fn default() -> Self {
+		Self {
+			scale: 4,
+			colouring: Colouring::Lineage,
+			density_radius: 4
+		}
+	}
+}
+
+impl FrameRenderer {
+	const BACKGROUND: [u8; 3] = [16, 16, 24];
+	const DENSE: [u8; 3] = [40, 72, 40];
+	const FOOD: Rgba<u8> = Rgba([96, 220, 96, 255]);
+
+	/// Draws the engine's current state, `scale` pixels per tile.
+	pub fn render(&self, engine: &Engine) -> RgbaImage {
+		let width = engine.config.size[0] as u32 + 1;
+		let height = engine.config.size[1] as u32 + 1;
+		let mut image = RgbaImage::new(width * self.scale, height * self.scale);
+
+		let density = self.food_density(engine, width as i32, height as i32);
+		for y in 0..height {
+			for x in 0..width {
+				let shade = density[(y * width + x) as usize];
+				let colour = Rgba([
+					lerp(FrameRenderer::BACKGROUND[0], FrameRenderer::DENSE[0], shade),
+					lerp(FrameRenderer::BACKGROUND[1], FrameRenderer::DENSE[1], shade),
+					lerp(FrameRenderer::BACKGROUND[2], FrameRenderer::DENSE[2], shade),
+					255
+				]);
+				self.fill_tile(&mut image, x, y, colour);
+			}
+		}
+
+		for position in engine.entities.keys() {
+			self.fill_tile(&mut image, position.x as u32, position.y as u32, FrameRenderer::FOOD);
+		}
+
+		for (agent, colour) in self.agent_colours(engine) {
+			self.fill_tile(&mut image, agent.position.x as u32, agent.position.y as u32, colour);
+		}
+
+		image
+	}
+
+	/// Plays the engine's current match to the end, rendering every `every`th round.
+	pub fn render_match(&self, engine: &mut Engine, every: usize) -> Result<Vec<RgbaImage>> {
+		let mut frames = vec![self.render(engine)];
+		while !engine.game_concluded {
+			engine.step()?;
+			if engine.round_idx.is_multiple_of(every.max(1)) || engine.game_concluded {
+				frames.push(self.render(engine));
+			}
+		}
+		Ok(frames)
+	}
+
+	/// Renders the start of a recorded match, every `every`th step and its last step, advancing one
+	/// rebuilt engine through the recording.
+	pub fn render_replay(&self, replay: &Replay, every: usize) -> Result<Vec<RgbaImage>> {
+		let mut engine = replay.engine_at(0)?;
+		let mut frames = vec![self.render(&engine)];
+		for step in 1..=replay.len() {
+			replay.apply_step(&mut engine)?;
+			if step.is_multiple_of(every.max(1)) || step == replay.len() {
+				frames.push(self.render(&engine));
+			}
+		}
+		Ok(frames)
+	}
+
+	/// Writes frame_00000.png, frame_00001.png, ... into `directory`.
+	pub fn save_frames(frames: &[RgbaImage], directory: &Path) -> Result<()> {
+		std::fs::create_dir_all(directory)?;
+		for (idx, frame) in frames.iter().enumerate() {
+			frame.save(directory.join(format!("frame_{:05}.png", idx)))?;
+		}
+		Ok(())
+	}
+
+	/// Writes `frames` as a looping GIF, showing each for `frame_delay_ms`.
+	pub fn save_gif(frames: &[RgbaImage], path: &Path, frame_delay_ms: u32) -> Result<()> {
+		let mut encoder = GifEncoder::new(BufWriter::new(File::create(path)?));
+		encoder.set_repeat(Repeat::Infinite)?;
+		Ok(encoder.encode_frames(frames.iter().map(|frame| {
+			Frame::from_parts(frame.clone(), 0, 0, Delay::from_numer_denom_ms(frame_delay_ms, 1))
+		}))?)
+	}
+
+	fn fill_tile(&self, image: &mut RgbaImage, x: u32, y: u32, colour: Rgba<u8>) {
+		for dy in 0..self.scale {
+			for dx in 0..self.scale {
+				image.put_pixel(x * self.scale + dx, y * self.scale + dy, colour);
+			}
+		}
+	}
+
+	// Share of food tiles within `density_radius` of every tile, normalised so the densest tile is 1.
+	fn food_density(&self, engine: &Engine, width: i32, height: i32) -> Vec<f32> {
+		// summed-area table, padded by one row and column
+		let stride = (width + 1) as usize;
+		let mut sums = vec![0u32; stride * (height + 1) as usize];
+		for y in 0..height {
+			for x in 0..width {
+				let food = engine.entities.contains_key(&Position::new(x, y)) as u32;
+				let idx = (y as usize + 1) * stride + x as usize + 1;
+				sums[idx] = food + sums[idx - 1] + sums[idx - stride] - sums[idx - stride - 1];
+			}
+		}
+
+		let radius = self.density_radius.max(0);
+		let counts = (0..height)
+			.flat_map(|y| (0..width).map(move |x| (x, y)))
+			.map(|(x, y)| {
+				let (left, right) = ((x - radius).max(0) as usize, (x + radius + 1).min(width) as usize);
+				let (top, bottom) = ((y - radius).max(0) as usize, (y + radius + 1).min(height) as usize);
+				sums[bottom * stride + right] + sums[top * stride + left]
+					- sums[top * stride + right] - sums[bottom * stride + left]
+			})
+			.collect::<Vec<u32>>();
+
+		let max = counts.iter().cloned().max().unwrap_or(0).max(1) as f32;
+		counts.into_iter().map(|count| count as f32 / max).collect()
+	}
+
+	fn agent_colours<'a>(&self, engine: &'a Engine) -> Vec<(&'a Agent, Rgba<u8>)> {
+		let mut agents = engine.agents.values().collect::<Vec<&Agent>>();
+		agents.sort_by_key(|agent| agent.id);
+
+		let groups = match self.colouring {
+			// agents without a genome are each coloured on their own
+			Colouring::Lineage => agents.iter()
+				.map(|agent| agent.genome.as_ref()
+					.map_or(agent.id, |genome| genome.ancestry.parents.first().cloned().unwrap_or(genome.id)))
+				.collect::<Vec<u64>>(),
+			Colouring::Species { threshold } => {
+				let weights = agents.iter()
+					.filter_map(|agent| agent.genome.as_ref())
+					.map(Genome::weights)
+					.collect::<Vec<Vec<f32>>>();
+				let mut species = assign_species(&weights, threshold).into_iter();
+				agents.iter()
+					.map(|agent| agent.genome.as_ref().and_then(|_| species.next()).map_or(agent.id, |species| species as u64))
+					.collect()
+			}
+		};
+
+		let mut palette = HashMap::new();
+		agents.into_iter()
+			.zip(groups)
+			.map(|(agent, group)| (agent, *palette.entry(group).or_insert_with(|| group_colour(group))))
+			.collect()
+	}
+}
+
+fn lerp(from: u8, to: u8, amount: f32) -> u8 {
+	(from as f32 + (to as f32 - from as f32) * amount).round() as u8
+}
+
+// A bright colour picked from the group's hash, so groups keep their colour across frames.
+fn group_colour(group: u64) -> Rgba<u8> {
+	let mut hasher = DefaultHasher::new();
+	group.hash(&mut hasher);
+	let hue = (hasher.finish() % 360) as f32;
+
+	let sector = hue / 60f32;
+	let x = 1f32 - (sector % 2f32 - 1f32).abs();
+	let (r, g, b) = match sector as u32 {
+		0 => (1f32, x, 0f32),
+		1 => (x, 1f32, 0f32),
+		2 => (0f32, 1f32, x),
+		3 => (0f32, x, 1f32),
+		4 => (x, 0f32, 1f32),
+		_ => (1f32, 0f32, x)
+	};
+	Rgba([(r * 255f32) as u8, (g * 255f32) as u8, (b * 255f32) as u8, 255])
+}
@@ -0,0 +1,70 @@
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod render_tests {
+	use std::collections::HashMap;
+	use std::fs;
+	use crate::backend::agent::Agent;
+	use crate::backend::engine::{Engine, EngineConfig};
+	use crate::backend::gene::Genome;
+	use crate::backend::Position;
+	use crate::interface::render::{Colouring, FrameRenderer};
+
+	fn engine() -> Engine {
+		let mut engine = Engine::new(EngineConfig {
+			food_spread: 2.5f64,
+			size: [20f64, 10f64],
+			round_max: 12,
+			agent_count: 3,
+			..EngineConfig::default()
+		}).unwrap();
+		let parent = Genome::blank(1);
+		let agents = [parent.copy(), parent.copy(), Genome::blank(2)].into_iter()
+			.enumerate()
+			.map(|(idx, genome)| (genome.id, Agent::new(genome.id, Position::new(idx as i32 * 4, 5), genome)))
+			.collect::<HashMap<u64, Agent>>();
+		engine.begin_match(agents, 0);
+		engine
+	}
+
+	#[test]
+	fn renders_match_to_gif() {
+		let mut engine = engine();
+		let renderer = FrameRenderer { scale: 2, colouring: Colouring::Species { threshold: 0.01 }, density_radius: 2 };
+
+		let first = renderer.render(&engine);
+		assert_eq!(first.dimensions(), (42, 22));
+
+		// siblings share a colour, the unrelated genome does not
+		let lineage = FrameRenderer::default();
+		let image = lineage.render(&engine);
+		let pixel = |x: u32| *image.get_pixel(x * lineage.scale, 5 * lineage.scale);
+		assert_eq!(pixel(0), pixel(4));
+		assert_ne!(pixel(0), pixel(8));
+
+		let frames = renderer.render_match(&mut engine, 5).unwrap();
+		assert!(engine.game_concluded);
+		assert_eq!(frames.len(), 4);
+
+		let path = std::env::temp_dir().join(format!("cadmus_render_{}.gif", std::process::id()));
+		FrameRenderer::save_gif(&frames, &path, 100).unwrap();
+		assert!(fs::metadata(&path).unwrap().len() > 0);
+		fs::remove_file(&path).unwrap();
+	}
+
+	#[test]
+	fn renders_replay_frames() {
+		let mut engine = engine();
+		engine.record_replay = true;
+		let agents = engine.agents.drain().collect::<HashMap<u64, Agent>>();
+		engine.play_match(agents, 0).unwrap();
+		let replay = engine.replay.take().unwrap();
+		assert_eq!(replay.len(), 12);
+
+		let renderer = FrameRenderer::default();
+		let frames = renderer.render_replay(&replay, 5).unwrap();
+		assert_eq!(frames.len(), 4);
+		assert_eq!(frames[0], renderer.render(&replay.engine_at(0).unwrap()));
+		assert_eq!(frames[2], renderer.render(&replay.engine_at(10).unwrap()));
+		assert_eq!(frames[3], renderer.render(&engine));
+	}
+}
@@ -0,0 +1,61 @@
+//! Cadmus evolves small neural networks that forage for food on a grid.
+//!
+//! An [`Engine`] simulates one world, described by an [`EngineConfig`], in which [`Agent`]s each
+//! controlled by a [`Genome`] move around and eat. An [`Orchestrator`] evolves a population of
+//! genomes, held in a [`GenomePool`], by playing them against each other in engines generation
+//! after generation. An [`Environment`] instead hands every agent's actions to the caller, one
+//! round at a time, for scripted policies, reinforcement learning or tests.
+//!
+//! ```
+//! use cadmus::{EngineConfig, Orchestrator, OrchestratorConfig};
+//!
+//! let engine_config = EngineConfig {
+//!     size: [20.0, 20.0],
+//!     round_max: 20,
+//!     agent_count: 6,
+//!     ..EngineConfig::default()
+//! };
+//! let config = OrchestratorConfig { trials: 1, ..OrchestratorConfig::default() };
+//! let mut orchestrator = Orchestrator::with_config(engine_config, config)?;
+//!
+//! let evaluations = orchestrator.run_generation(0)?;
+//! for id in Orchestrator::population(&evaluations) {
+//!     let genome = orchestrator.genepool.get_genome(id)?;
+//!     println!("genome {} ate {}", genome.id, evaluations[&id].food);
+//! }
+//! # Ok::<(), cadmus::Error>(())
+//! ```
+//!
+//! Everything the `cadmus` binary does is available through [`cli::Cli`].
+
+mod backend;
+mod interface;
+/// The command line interface of the `cadmus` binary.
+pub mod cli;
+mod cli_tests;
+
+pub use crate::backend::agent::{Agent, AgentSense, AgentStats};
+pub use crate::backend::archive::{GenomeArchive, GenomeRecord};
+pub use crate::backend::baseline::Baseline;
+pub use crate::backend::behaviour::{Behaviour, NoveltyArchive, NoveltyConfig};
+pub use crate::backend::checkpoint::Checkpoint;
+pub use crate::backend::config::ExperimentConfig;
+pub use crate::backend::engine::{Engine, EngineConfig, MatchResult};
+pub use crate::backend::entity::{Entity, EntityType};
+pub use crate::backend::environment::{Environment, Transition};
+pub use crate::backend::error::{Error, Result};
+pub use crate::backend::evaluation::{Aggregation, Evaluation, FitnessMode};
+pub use crate::backend::gene::{Ancestry, Genome, GenomePool, GenomeSummary, MatchStats, RetentionPolicy};
+pub use crate::backend::island::{Archipelago, IslandConfig, Topology};
+pub use crate::backend::lineage::{EvaluationRecord, Lineage, LineageNode};
+pub use crate::backend::map::{Action, Direction};
+pub use crate::backend::metrics::{CsvSink, GenerationMetrics, JsonLinesSink, MetricsFormat, MetricsSink};
+pub use crate::backend::mutation::MutationOperator;
+pub use crate::backend::network::NetworkShape;
+pub use crate::backend::optimizer::{Optimizer, OptimizerKind};
+pub use crate::backend::policy::{Policy, PolicyMode};
+pub use crate::backend::replay::{AgentFrame, AgentStart, Replay, ReplayHeader, ReplayStep};
+pub use crate::backend::sweep::{ParameterSpace, Point, SweepConfig, SweepResult, SweepRun};
+pub use crate::backend::{Orchestrator, OrchestratorConfig, Position};
+pub use crate::interface::render::{Colouring, FrameRenderer};
+pub use crate::interface::Visualiser;
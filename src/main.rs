@@ -1,12 +1,9 @@
-use crate::backend::Orchestrator;
-
-#[allow(dead_code)]
-
-mod interface;
-mod backend;
+use cadmus::cli::Cli;
+use clap::Parser;
 
 fn main() {
-    let mut orchestrator = Orchestrator::new();
-    orchestrator.start_matches()
-    // println!("{:}", eng.id)
+    if let Err(error) = Cli::parse().run() {
+        eprintln!("error: {}", error);
+        std::process::exit(1);
+    }
 }